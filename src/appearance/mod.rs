@@ -0,0 +1,237 @@
+//! Client for the `org.freedesktop.portal.Settings` interface of the XDG desktop portal, which
+//! exposes the desktop-wide light/dark preference.
+
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedValue, Value};
+
+use crate::Result;
+
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const SETTINGS_INTERFACE: &str = "org.freedesktop.portal.Settings";
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorScheme {
+	NoPreference,
+	PreferDark,
+	PreferLight,
+}
+
+impl ColorScheme {
+	fn from_value(value: &Value<'_>) -> Result<Self> {
+		match value {
+			// `Read` wraps the setting in an extra variant.
+			Value::Value(inner) => Self::from_value(inner),
+			Value::U32(0) => Ok(Self::NoPreference),
+			Value::U32(1) => Ok(Self::PreferDark),
+			Value::U32(2) => Ok(Self::PreferLight),
+			other => Err(format!("Unexpected color-scheme value {other:?}").into()),
+		}
+	}
+
+	pub fn is_dark(self) -> bool {
+		self == Self::PreferDark
+	}
+}
+
+/// Connection to the settings portal on the session bus named by `DBUS_SESSION_BUS_ADDRESS`.
+pub struct Portal {
+	proxy: Proxy<'static>,
+}
+
+impl Portal {
+	pub fn connect() -> Result<Self> {
+		Self::on(&Connection::session()?)
+	}
+
+	fn on(connection: &Connection) -> Result<Self> {
+		let proxy = Proxy::new(connection, PORTAL_DESTINATION, PORTAL_PATH, SETTINGS_INTERFACE)?;
+		Ok(Self { proxy })
+	}
+
+	pub fn color_scheme(&self) -> Result<ColorScheme> {
+		let value: OwnedValue = self
+			.proxy
+			.call("ReadOne", &(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY))
+			.or_else(|_| self.proxy.call("Read", &(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY)))?;
+		ColorScheme::from_value(&value)
+	}
+
+	/// Blocks, calling `on_change` for every color-scheme change until it returns an error or the
+	/// bus connection is lost.
+	pub fn watch(&self, mut on_change: impl FnMut(ColorScheme) -> Result<()>) -> Result<()> {
+		let signals = self
+			.proxy
+			.receive_signal_with_args("SettingChanged", &[(0, APPEARANCE_NAMESPACE), (1, COLOR_SCHEME_KEY)])?;
+
+		for message in signals {
+			let (_, _, value): (String, String, OwnedValue) = message.body().deserialize()?;
+			on_change(ColorScheme::from_value(&value)?)?;
+		}
+
+		Err("Lost connection to the session bus".into())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::{BufRead, BufReader};
+	use std::process::{Child, Command, Stdio};
+	use std::sync::mpsc;
+	use std::thread;
+	use std::time::Duration;
+
+	use zbus::blocking::connection::Builder;
+	use zbus::fdo;
+
+	#[test]
+	fn color_scheme_from_value() {
+		assert_eq!(
+			ColorScheme::from_value(&Value::U32(0)).unwrap(),
+			ColorScheme::NoPreference
+		);
+		assert_eq!(
+			ColorScheme::from_value(&Value::U32(1)).unwrap(),
+			ColorScheme::PreferDark
+		);
+		assert_eq!(
+			ColorScheme::from_value(&Value::U32(2)).unwrap(),
+			ColorScheme::PreferLight
+		);
+		assert!(ColorScheme::from_value(&Value::U32(3)).is_err());
+		assert!(ColorScheme::from_value(&Value::from("dark")).is_err());
+	}
+
+	#[test]
+	fn color_scheme_from_nested_value() {
+		let read = Value::Value(Box::new(Value::U32(1)));
+		assert_eq!(ColorScheme::from_value(&read).unwrap(), ColorScheme::PreferDark);
+		let nested = Value::Value(Box::new(read));
+		assert_eq!(ColorScheme::from_value(&nested).unwrap(), ColorScheme::PreferDark);
+		assert!(ColorScheme::from_value(&Value::Value(Box::new(Value::I32(1)))).is_err());
+	}
+
+	/// A private bus, stopped when dropped.
+	struct Bus(Child, String);
+
+	impl Bus {
+		fn start() -> Self {
+			let mut child = Command::new("dbus-daemon")
+				.args(["--session", "--nofork", "--print-address"])
+				.stdout(Stdio::piped())
+				.stderr(Stdio::null())
+				.spawn()
+				.expect("dbus-daemon should be installed");
+			let mut address = String::new();
+			BufReader::new(child.stdout.take().unwrap())
+				.read_line(&mut address)
+				.unwrap();
+			Self(child, address.trim().to_string())
+		}
+	}
+
+	impl Drop for Bus {
+		fn drop(&mut self) {
+			let _ = self.0.kill();
+			let _ = self.0.wait();
+		}
+	}
+
+	/// Stand-in for the portal's settings. Without `ReadOne`, it answers like portals from before
+	/// version 2, which only have `Read`.
+	struct FakeSettings {
+		scheme: u32,
+		read_one: bool,
+	}
+
+	#[zbus::interface(name = "org.freedesktop.portal.Settings")]
+	impl FakeSettings {
+		fn read_one(&self, namespace: &str, key: &str) -> fdo::Result<OwnedValue> {
+			if !self.read_one {
+				return Err(fdo::Error::UnknownMethod("ReadOne".into()));
+			}
+			self.check(namespace, key)?;
+			Ok(OwnedValue::from(self.scheme))
+		}
+
+		fn read(&self, namespace: &str, key: &str) -> fdo::Result<OwnedValue> {
+			self.check(namespace, key)?;
+			Value::Value(Box::new(Value::U32(self.scheme)))
+				.try_to_owned()
+				.map_err(|err| fdo::Error::Failed(err.to_string()))
+		}
+	}
+
+	impl FakeSettings {
+		fn check(&self, namespace: &str, key: &str) -> fdo::Result<()> {
+			if (namespace, key) == (APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY) {
+				Ok(())
+			} else {
+				Err(fdo::Error::Failed(format!("Unknown setting {namespace}.{key}")))
+			}
+		}
+	}
+
+	fn serve(bus: &Bus, settings: FakeSettings) -> Connection {
+		Builder::address(bus.1.as_str())
+			.unwrap()
+			.name(PORTAL_DESTINATION)
+			.unwrap()
+			.serve_at(PORTAL_PATH, settings)
+			.unwrap()
+			.build()
+			.unwrap()
+	}
+
+	fn client(bus: &Bus) -> Portal {
+		let connection = Builder::address(bus.1.as_str()).unwrap().build().unwrap();
+		Portal::on(&connection).unwrap()
+	}
+
+	#[test]
+	#[ignore = "starts a dbus-daemon"]
+	fn portal_reads_and_watches_color_scheme() {
+		let bus = Bus::start();
+		let server = serve(
+			&bus,
+			FakeSettings {
+				scheme: 1,
+				read_one: true,
+			},
+		);
+		let portal = client(&bus);
+		assert_eq!(portal.color_scheme().unwrap(), ColorScheme::PreferDark);
+
+		let (sender, receiver) = mpsc::channel();
+		thread::spawn(move || {
+			let _ = portal.watch(|scheme| sender.send(scheme).map_err(Into::into));
+		});
+
+		// The watcher subscribes on its own thread, so announce the change until it is seen.
+		let change = (APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY, Value::U32(2));
+		let scheme = (0..50).find_map(|_| {
+			server
+				.emit_signal(None::<&str>, PORTAL_PATH, SETTINGS_INTERFACE, "SettingChanged", &change)
+				.unwrap();
+			receiver.recv_timeout(Duration::from_millis(100)).ok()
+		});
+		assert_eq!(scheme, Some(ColorScheme::PreferLight));
+	}
+
+	#[test]
+	#[ignore = "starts a dbus-daemon"]
+	fn portal_falls_back_to_read() {
+		let bus = Bus::start();
+		let _server = serve(
+			&bus,
+			FakeSettings {
+				scheme: 2,
+				read_one: false,
+			},
+		);
+		assert_eq!(client(&bus).color_scheme().unwrap(), ColorScheme::PreferLight);
+	}
+}
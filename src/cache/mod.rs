@@ -0,0 +1,112 @@
+//! On-disk cache of parsed themes in `$XDG_CACHE_HOME/alacritty_themes`, so that large libraries
+//! are not parsed again on every start. Theme files are looked up by path and reparsed when their
+//! size or modification time changes; bundled themes are reparsed when their content does.
+
+use dirs::cache_dir;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::store::Parsed;
+use crate::Result;
+
+/// Identifies one version of a file.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stamp {
+	pub size: u64,
+	/// Modification time in nanoseconds since the epoch.
+	pub modified: Option<u128>,
+	/// SHA-256 of the content, for bundled themes, which have no modification time.
+	#[serde(default)]
+	pub checksum: Option<[u8; 32]>,
+}
+
+impl Stamp {
+	/// `None` if the file can't be inspected, which also covers missing files.
+	pub fn of(path: &Path) -> Option<Self> {
+		let metadata = fs::metadata(path).ok()?;
+		let modified = metadata
+			.modified()
+			.ok()
+			.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+			.map(|duration| duration.as_nanos());
+		Some(Self {
+			size: metadata.len(),
+			modified,
+			checksum: None,
+		})
+	}
+
+	pub fn embedded(content: &[u8]) -> Self {
+		Self {
+			size: content.len() as u64,
+			modified: None,
+			checksum: Some(Sha256::digest(content).into()),
+		}
+	}
+}
+
+/// The stamps a theme was parsed from: the theme file and its metadata sidecar, if any.
+pub type Stamps = (Stamp, Option<Stamp>);
+
+#[derive(Serialize, Deserialize)]
+struct Cached {
+	stamps: Stamps,
+	parsed: std::result::Result<Parsed, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Cache {
+	version: String,
+	themes: HashMap<String, Cached>,
+}
+
+impl Cache {
+	pub fn path() -> Option<PathBuf> {
+		cache_dir().map(|path| path.join("alacritty_themes/index.json"))
+	}
+
+	pub fn new() -> Self {
+		Self {
+			version: env!("CARGO_PKG_VERSION").to_string(),
+			themes: HashMap::new(),
+		}
+	}
+
+	/// Loads the cache, starting afresh if it is missing, unreadable or from another version.
+	pub fn load() -> Self {
+		Self::path()
+			.and_then(|path| fs::read(path).ok())
+			.and_then(|content| serde_json::from_slice::<Self>(&content).ok())
+			.filter(|cache| cache.version == env!("CARGO_PKG_VERSION"))
+			.unwrap_or_else(Self::new)
+	}
+
+	pub fn save(&self) -> Result<()> {
+		let path = Self::path().ok_or("Could not determine XDG_CACHE directory")?;
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		fs::write(path, serde_json::to_vec(self)?)?;
+		Ok(())
+	}
+
+	pub fn len(&self) -> usize {
+		self.themes.len()
+	}
+
+	/// The parse result for `key`, if it was cached from the same stamps.
+	pub fn get(&self, key: &str, stamps: Stamps) -> Option<&std::result::Result<Parsed, String>> {
+		self.themes
+			.get(key)
+			.filter(|cached| cached.stamps == stamps)
+			.map(|cached| &cached.parsed)
+	}
+
+	pub fn insert(&mut self, key: String, stamps: Stamps, parsed: std::result::Result<Parsed, String>) {
+		self.themes.insert(key, Cached { stamps, parsed });
+	}
+}
@@ -0,0 +1,293 @@
+use crate::commands::fav::FavAction;
+use crate::commands::filter::FilterAction;
+use crate::commands::list::ListOptions;
+use crate::commands::random::{RandomOptions, DEFAULT_NO_REPEAT};
+use crate::commands::tag::TagAction;
+use crate::daemon::DaemonOptions;
+use crate::Result;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: alacritty-themes-rs [COMMAND]
+
+Commands:
+  (none) [OPTIONS]    Browse and preview themes interactively
+      --sample FILE       Also preview with terminal output captured with its colors, as
+                          recorded by 'script' or 'tmux capture-pane -e'
+      --preview-file FILE Preview with a source file, highlighted, instead of the Rust snippet
+  list [OPTIONS]      List all themes with the search path layer they come from
+      --tag TAG           Only list themes with this tag (repeatable)
+      --filter EXPR       Only list themes matching a filter expression
+      --json              Print everything known about the themes as JSON
+  info NAME [--json]  Show a theme's metadata, tags and origin
+  check               Validate all themes, exiting with an error if any is broken
+  random [OPTIONS]    Apply a random theme
+      --dark              Only consider dark themes
+      --light             Only consider light themes
+      --min-contrast N    Minimum foreground/background contrast ratio
+      --tag TAG           Only consider themes with this tag (repeatable)
+      --favorites         Only consider favorite themes
+      --filter EXPR       Only consider themes matching a filter expression
+      --no-repeat N       Avoid the last N picks (default: 10)
+  schedule [--once]   Switch themes according to the schedule in alacritty_themes.toml
+      --once              Apply the currently scheduled theme and exit
+  sync [--dry-run]    Install new bundled themes and update unmodified ones, keeping local edits
+      --dry-run           Only show what would change
+  toggle              Switch the current theme to its light/dark counterpart
+  fav add NAME...     Add themes to the favorites
+  fav remove NAME...  Remove themes from the favorites
+  fav list            List the favorite themes
+  tag add NAME TAG... Tag a theme
+  tag remove NAME TAG...
+                      Remove tags from a theme
+  tag list [NAME]     List all tags, or the tags of one theme
+  filter save NAME EXPR
+                      Save a filter expression, to be used as '@NAME'
+  filter remove NAME  Remove a saved filter
+  filter list         List the saved filters
+  follow-system       Switch between the [appearance] light and dark themes following the
+                      desktop's color-scheme preference (XDG settings portal)
+  daemon [OPTIONS]    Run in the background and accept commands on a Unix socket
+      --schedule          Also switch themes according to the schedule
+      --follow-system     Also follow the desktop's color-scheme preference
+  ctl COMMAND         Send a command to the daemon: apply NAME, next, prev,
+                      random [OPTIONS], toggle, toggle-dark-light, current
+  help                Show this message
+
+Filter expressions combine the terms dark, light, favorite, tag:TAG, name:TEXT, contrast>N
+(also <, <=, =, >=) and @SAVED with !, &&, || and parentheses, e.g. 'dark && contrast>7'.";
+
+#[derive(Default)]
+pub struct BrowseOptions {
+	/// A capture of terminal output to preview themes with.
+	pub sample: Option<PathBuf>,
+	/// A source file to preview themes with.
+	pub preview_file: Option<PathBuf>,
+}
+
+pub enum Command {
+	Browse(BrowseOptions),
+	List(ListOptions),
+	Info { name: String, json: bool },
+	Check,
+	Random(RandomOptions),
+	Schedule { once: bool },
+	Toggle,
+	Fav(FavAction),
+	Tag(TagAction),
+	Filter(FilterAction),
+	Sync { dry_run: bool },
+	FollowSystem,
+	Daemon(DaemonOptions),
+	Ctl(String),
+	Help,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command> {
+	let mut args = args.into_iter();
+
+	let first = args.next();
+	match first.as_deref() {
+		None => Ok(Command::Browse(BrowseOptions::default())),
+		Some(option) if option.starts_with("--") && option != "--help" => {
+			parse_browse(first.into_iter().chain(args)).map(Command::Browse)
+		},
+		Some("list") => parse_list(args).map(Command::List),
+		Some("info") => parse_info(args),
+		Some("check") => no_options("check", args).map(|()| Command::Check),
+		Some("random") => parse_random(args).map(Command::Random),
+		Some("schedule") => parse_schedule(args),
+		Some("sync") => parse_sync(args),
+		Some("toggle") => no_options("toggle", args).map(|()| Command::Toggle),
+		Some("fav") => parse_fav(args).map(Command::Fav),
+		Some("tag") => parse_tag(args).map(Command::Tag),
+		Some("filter") => parse_filter(args).map(Command::Filter),
+		Some("follow-system") => no_options("follow-system", args).map(|()| Command::FollowSystem),
+		Some("daemon") => parse_daemon(args).map(Command::Daemon),
+		Some("ctl") => {
+			let request = args.collect::<Vec<_>>().join(" ");
+			if request.is_empty() {
+				return Err("'ctl' requires a command".into());
+			}
+			Ok(Command::Ctl(request))
+		},
+		Some("help" | "-h" | "--help") => Ok(Command::Help),
+		Some(other) => Err(format!("Unknown command '{other}'\n\n{USAGE}").into()),
+	}
+}
+
+pub fn parse_random(mut args: impl Iterator<Item = String>) -> Result<RandomOptions> {
+	let mut options = RandomOptions {
+		no_repeat: DEFAULT_NO_REPEAT,
+		..RandomOptions::default()
+	};
+
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--dark" => options.dark = true,
+			"--light" => options.light = true,
+			"--favorites" => options.favorites = true,
+			"--min-contrast" => options.min_contrast = Some(parse_value(&arg, args.next())?),
+			"--tag" => options.tags.push(required_value(&arg, args.next())?),
+			"--filter" => options.filter = Some(required_value(&arg, args.next())?),
+			"--no-repeat" => options.no_repeat = parse_value(&arg, args.next())?,
+			other => return Err(format!("Unknown option '{other}' for 'random'").into()),
+		}
+	}
+
+	if options.dark && options.light {
+		return Err("'--dark' and '--light' cannot be used together".into());
+	}
+
+	Ok(options)
+}
+
+fn parse_browse(mut args: impl Iterator<Item = String>) -> Result<BrowseOptions> {
+	let mut options = BrowseOptions::default();
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--sample" => options.sample = Some(required_value(&arg, args.next())?.into()),
+			"--preview-file" => options.preview_file = Some(required_value(&arg, args.next())?.into()),
+			other => return Err(format!("Unknown option '{other}'\n\n{USAGE}").into()),
+		}
+	}
+
+	Ok(options)
+}
+
+fn parse_list(mut args: impl Iterator<Item = String>) -> Result<ListOptions> {
+	let mut options = ListOptions::default();
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--tag" => options.tags.push(required_value(&arg, args.next())?),
+			"--filter" => options.filter = Some(required_value(&arg, args.next())?),
+			"--json" => options.json = true,
+			other => return Err(format!("Unknown option '{other}' for 'list'").into()),
+		}
+	}
+
+	Ok(options)
+}
+
+fn parse_info(args: impl Iterator<Item = String>) -> Result<Command> {
+	let mut name = None;
+	let mut json = false;
+	for arg in args {
+		match arg.as_str() {
+			"--json" => json = true,
+			other if other.starts_with("--") || name.is_some() => {
+				return Err(format!("Unknown option '{other}' for 'info'").into());
+			},
+			_ => name = Some(arg),
+		}
+	}
+
+	let name = name.ok_or("'info' requires a theme name")?;
+	Ok(Command::Info { name, json })
+}
+
+fn parse_schedule(args: impl Iterator<Item = String>) -> Result<Command> {
+	let mut once = false;
+	for arg in args {
+		match arg.as_str() {
+			"--once" => once = true,
+			other => return Err(format!("Unknown option '{other}' for 'schedule'").into()),
+		}
+	}
+
+	Ok(Command::Schedule { once })
+}
+
+fn parse_sync(args: impl Iterator<Item = String>) -> Result<Command> {
+	let mut dry_run = false;
+	for arg in args {
+		match arg.as_str() {
+			"--dry-run" => dry_run = true,
+			other => return Err(format!("Unknown option '{other}' for 'sync'").into()),
+		}
+	}
+
+	Ok(Command::Sync { dry_run })
+}
+
+fn parse_fav(mut args: impl Iterator<Item = String>) -> Result<FavAction> {
+	let action = args.next();
+	let names: Vec<String> = args.collect();
+	match action.as_deref() {
+		Some("add" | "remove") if names.is_empty() => {
+			Err(format!("'fav {}' requires at least one theme name", action.unwrap_or_default()).into())
+		},
+		Some("add") => Ok(FavAction::Add(names)),
+		Some("remove") => Ok(FavAction::Remove(names)),
+		Some("list") => no_options("fav list", names.into_iter()).map(|()| FavAction::List),
+		Some(other) => Err(format!("Unknown action '{other}' for 'fav'").into()),
+		None => Err("'fav' requires an action: add, remove or list".into()),
+	}
+}
+
+fn parse_tag(mut args: impl Iterator<Item = String>) -> Result<TagAction> {
+	let action = args.next();
+	let name = args.next();
+	let tags: Vec<String> = args.collect();
+	match (action.as_deref(), name) {
+		(Some(action @ ("add" | "remove")), name) if name.is_none() || tags.is_empty() => {
+			Err(format!("'tag {action}' requires a theme name and at least one tag").into())
+		},
+		(Some("add"), Some(name)) => Ok(TagAction::Add(name, tags)),
+		(Some("remove"), Some(name)) => Ok(TagAction::Remove(name, tags)),
+		(Some("list"), name) => no_options("tag list", tags.into_iter()).map(|()| TagAction::List(name)),
+		(Some(other), _) => Err(format!("Unknown action '{other}' for 'tag'").into()),
+		(None, _) => Err("'tag' requires an action: add, remove or list".into()),
+	}
+}
+
+fn parse_filter(mut args: impl Iterator<Item = String>) -> Result<FilterAction> {
+	match args.next().as_deref() {
+		Some("save") => {
+			let name = required_value("filter save", args.next())?;
+			let expression = args.collect::<Vec<_>>().join(" ");
+			if expression.is_empty() {
+				return Err("'filter save' requires a name and an expression".into());
+			}
+			Ok(FilterAction::Save(name, expression))
+		},
+		Some("remove") => {
+			let name = required_value("filter remove", args.next())?;
+			no_options("filter remove", args).map(|()| FilterAction::Remove(name))
+		},
+		Some("list") => no_options("filter list", args).map(|()| FilterAction::List),
+		Some(other) => Err(format!("Unknown action '{other}' for 'filter'").into()),
+		None => Err("'filter' requires an action: save, remove or list".into()),
+	}
+}
+
+fn parse_daemon(args: impl Iterator<Item = String>) -> Result<DaemonOptions> {
+	let mut options = DaemonOptions::default();
+	for arg in args {
+		match arg.as_str() {
+			"--schedule" => options.schedule = true,
+			"--follow-system" => options.follow_system = true,
+			other => return Err(format!("Unknown option '{other}' for 'daemon'").into()),
+		}
+	}
+
+	Ok(options)
+}
+
+fn no_options(command: &str, mut args: impl Iterator<Item = String>) -> Result<()> {
+	match args.next() {
+		Some(other) => Err(format!("Unknown option '{other}' for '{command}'").into()),
+		None => Ok(()),
+	}
+}
+
+fn required_value(flag: &str, value: Option<String>) -> Result<String> {
+	value.ok_or_else(|| format!("'{flag}' requires a value").into())
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T> {
+	let value = required_value(flag, value)?;
+	value
+		.parse()
+		.map_err(|_| format!("Invalid value '{value}' for '{flag}'").into())
+}
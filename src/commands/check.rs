@@ -0,0 +1,42 @@
+use crate::store::{ThemeEntry, ThemeStore};
+use crate::Result;
+
+/// Loads every theme and reports those that fail, along with the base theme if it is missing.
+/// Themes whose metadata is invalid are reported too, but as they still load they don't count
+/// as broken.
+pub fn run(store: &ThemeStore, base: Option<&str>) -> Result<()> {
+	let mut broken = 0;
+	let mut invalid_metadata = 0;
+	for entry in store.entries() {
+		if let Err(err) = store.load(entry) {
+			broken += 1;
+			report(entry, &err.to_string());
+		} else if let Some(err) = entry.metadata_error() {
+			invalid_metadata += 1;
+			report(entry, err);
+		}
+	}
+
+	let count = store.entries().len();
+	if let Some(base) = base.filter(|base| store.find(base).is_none()) {
+		return Err(format!("Base theme '{base}' not found, and {broken} of {count} themes are broken").into());
+	}
+	if broken > 0 {
+		return Err(format!("{broken} of {count} themes are broken").into());
+	}
+	match invalid_metadata {
+		0 => println!("All {count} themes are valid"),
+		_ => println!("All {count} themes load, {invalid_metadata} of them with invalid metadata"),
+	}
+	Ok(())
+}
+
+fn report(entry: &ThemeEntry, err: &str) {
+	match entry.path() {
+		Some(path) => println!("{} ({}):", entry.name, path.display()),
+		None => println!("{}:", entry.name),
+	}
+	for line in err.lines() {
+		println!("  {line}");
+	}
+}
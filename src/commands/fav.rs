@@ -0,0 +1,44 @@
+use crate::state::State;
+use crate::store::ThemeStore;
+use crate::Result;
+
+pub enum FavAction {
+	Add(Vec<String>),
+	Remove(Vec<String>),
+	List,
+}
+
+pub fn run(action: &FavAction, store: &ThemeStore) -> Result<()> {
+	let mut state = State::load()?;
+
+	match action {
+		FavAction::Add(names) => {
+			for name in names {
+				let entry = store.get(name)?;
+				if !state.add_favorite(&entry.name) {
+					println!("'{}' already is a favorite", entry.name);
+				}
+			}
+			state.save()
+		},
+		FavAction::Remove(names) => {
+			for name in names {
+				// Themes that have been deleted can still be removed by their old name.
+				let name = store.find(name).map_or(name.as_str(), |entry| entry.name.as_str());
+				if !state.remove_favorite(name) {
+					println!("'{name}' is not a favorite");
+				}
+			}
+			state.save()
+		},
+		FavAction::List => {
+			for name in &state.favorites {
+				match store.find(name) {
+					Some(entry) => println!("{name}  {}", entry.display_name()),
+					None => println!("{name}  (missing)"),
+				}
+			}
+			Ok(())
+		},
+	}
+}
@@ -0,0 +1,45 @@
+use crate::filter::Filter;
+use crate::state::State;
+use crate::Result;
+
+pub enum FilterAction {
+	Save(String, String),
+	Remove(String),
+	List,
+}
+
+pub fn run(action: &FilterAction) -> Result<()> {
+	let mut state = State::load()?;
+
+	match action {
+		FilterAction::Save(name, expression) => {
+			if name.is_empty() || name.contains(|c: char| c.is_whitespace() || "()!&|<>=@".contains(c)) {
+				return Err(format!("Invalid filter name '{name}'").into());
+			}
+			// Validate before saving, including references to other saved filters.
+			let previous = state.filters.insert(name.clone(), expression.clone());
+			if let Err(err) = Filter::parse(expression, &state) {
+				match previous {
+					Some(previous) => state.filters.insert(name.clone(), previous),
+					None => state.filters.remove(name),
+				};
+				return Err(err);
+			}
+			state.save()
+		},
+		FilterAction::Remove(name) => {
+			state
+				.filters
+				.remove(name)
+				.ok_or_else(|| format!("Unknown saved filter '{name}'"))?;
+			state.save()
+		},
+		FilterAction::List => {
+			let width = state.filters.keys().map(String::len).max().unwrap_or(0);
+			for (name, expression) in &state.filters {
+				println!("{name:width$}  {expression}");
+			}
+			Ok(())
+		},
+	}
+}
@@ -0,0 +1,23 @@
+use crate::appearance::{ColorScheme, Portal};
+use crate::settings::Settings;
+use crate::Result;
+
+/// Applies the theme matching the desktop's current light/dark preference through `apply`, then
+/// keeps following changes announced by the settings portal.
+pub fn run(settings: &Settings, mut apply: impl FnMut(&str) -> Result<()>) -> Result<()> {
+	let appearance = settings.appearance.as_ref().ok_or_else(|| {
+		let path = Settings::path().unwrap_or_default();
+		format!("No [appearance] light/dark themes configured in {}", path.display())
+	})?;
+
+	let portal = Portal::connect()?;
+	let mut follow = |scheme: ColorScheme| {
+		let theme = appearance.theme_for(scheme);
+		apply(theme)?;
+		println!("{scheme:?}: {theme}");
+		Ok(())
+	};
+
+	follow(portal.color_scheme()?)?;
+	portal.watch(follow)
+}
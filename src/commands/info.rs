@@ -0,0 +1,107 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::filter::theme_tags;
+use crate::state::State;
+use crate::store::{ThemeEntry, ThemeStore};
+use crate::structured_data::structs::Metadata;
+use crate::utils::color::Stats;
+use crate::Result;
+
+/// Everything known about a theme, as printed by `info` and `list --json`.
+#[derive(Serialize)]
+pub struct ThemeInfo {
+	pub name: String,
+	pub title: String,
+	pub origin: &'static str,
+	pub path: Option<PathBuf>,
+	pub shadows: Vec<&'static str>,
+	/// Tags from the metadata and the user's own.
+	pub tags: Vec<String>,
+	pub favorite: bool,
+	pub metadata: Metadata,
+	pub stats: Option<Stats>,
+	/// Why the theme can't be loaded, if it can't.
+	pub error: Option<String>,
+	/// Why the metadata can't be read, if it can't. The theme loads regardless.
+	pub metadata_error: Option<String>,
+}
+
+impl ThemeInfo {
+	/// Broken metadata is reported in `metadata_error`, with the fields it would set missing.
+	pub fn new(store: &ThemeStore, entry: &ThemeEntry, state: &State) -> Self {
+		Self {
+			name: entry.name.clone(),
+			title: entry.display_name(),
+			origin: entry.origin.label(),
+			path: entry.path().map(PathBuf::from),
+			shadows: entry.shadows.iter().map(|origin| origin.label()).collect(),
+			tags: theme_tags(entry, state),
+			favorite: state.is_favorite(&entry.name),
+			metadata: entry.metadata().unwrap_or_default(),
+			stats: entry.stats().copied(),
+			error: store.load(entry).err().map(|err| err.to_string()),
+			metadata_error: entry.metadata_error().map(str::to_string),
+		}
+	}
+
+	/// Labelled lines for display, leaving out whatever is unknown.
+	pub fn fields(&self) -> Vec<(&'static str, String)> {
+		let mut fields = vec![("Name", self.title.clone()), ("Theme", self.name.clone())];
+		let metadata = &self.metadata;
+		let optional = [
+			("Author", metadata.author.clone()),
+			("URL", metadata.url.clone()),
+			("License", metadata.license.clone()),
+			("Variant", metadata.variant.map(|variant| variant.label().to_string())),
+			("Pair", metadata.pair.clone()),
+			("Tags", Some(self.tags.join(", ")).filter(|tags| !tags.is_empty())),
+		];
+		fields.extend(optional.into_iter().filter_map(|(label, value)| Some((label, value?))));
+
+		if let Some(stats) = &self.stats {
+			let mut colors = format!("contrast {:.1}, luminance {:.2}", stats.contrast, stats.luminance);
+			if let Some(hue) = stats.hue {
+				colors.push_str(&format!(", hue {hue:.0}°"));
+			}
+			fields.push(("Colors", colors));
+		}
+
+		let mut origin = self.origin.to_string();
+		if let Some(path) = &self.path {
+			origin.push_str(&format!(" ({})", path.display()));
+		}
+		fields.push(("Origin", origin));
+		if !self.shadows.is_empty() {
+			fields.push(("Shadows", self.shadows.join(", ")));
+		}
+		for (label, error) in [("Error", &self.error), ("Warning", &self.metadata_error)] {
+			if let Some(error) = error {
+				let mut lines = error.lines().map(str::to_string);
+				fields.push((label, lines.next().unwrap_or_default()));
+				fields.extend(lines.map(|line| ("", line)));
+			}
+		}
+		fields
+	}
+}
+
+pub fn run(name: &str, json: bool, store: &ThemeStore) -> Result<()> {
+	let info = ThemeInfo::new(store, store.get(name)?, &State::load()?);
+
+	if json {
+		println!("{}", serde_json::to_string_pretty(&info)?);
+		return Ok(());
+	}
+
+	for (label, value) in info.fields() {
+		// Unlabelled fields continue the one above.
+		let label = if label.is_empty() {
+			String::new()
+		} else {
+			format!("{label}:")
+		};
+		println!("{label:8} {value}");
+	}
+	Ok(())
+}
@@ -0,0 +1,63 @@
+use crate::commands::info::ThemeInfo;
+use crate::filter::Filter;
+use crate::state::State;
+use crate::store::{Origin, ThemeStore};
+use crate::structured_data::structs::Colors;
+use crate::Result;
+
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+	pub tags: Vec<String>,
+	/// A filter expression, see [`Filter`].
+	pub filter: Option<String>,
+	pub json: bool,
+}
+
+pub fn run(options: &ListOptions, store: &ThemeStore) -> Result<()> {
+	let state = State::load()?;
+	let mut filters: Vec<Filter> = options.tags.iter().cloned().map(Filter::Tag).collect();
+	if let Some(expression) = &options.filter {
+		filters.push(Filter::parse(expression, &state)?);
+	}
+	let filter = Filter::All(filters);
+
+	let entries: Vec<_> = store
+		.entries()
+		.iter()
+		.filter(|entry| filter.matches_entry(store, entry, &Colors::default(), &state))
+		.collect();
+
+	if options.json {
+		let infos: Vec<ThemeInfo> = entries
+			.iter()
+			.map(|entry| ThemeInfo::new(store, entry, &state))
+			.collect();
+		println!("{}", serde_json::to_string_pretty(&infos)?);
+		return Ok(());
+	}
+
+	let width = entries.iter().map(|entry| entry.name.len()).max().unwrap_or(0);
+	let title_width = entries
+		.iter()
+		.map(|entry| entry.display_name().chars().count())
+		.max()
+		.unwrap_or(0);
+
+	for entry in entries {
+		let mut line = format!(
+			"{:width$}  {:title_width$}  {}",
+			entry.name,
+			entry.display_name(),
+			entry.origin.label()
+		);
+		if let Some(path) = entry.path() {
+			line.push_str(&format!("  {}", path.display()));
+		}
+		if !entry.shadows.is_empty() {
+			let shadows: Vec<&str> = entry.shadows.iter().copied().map(Origin::label).collect();
+			line.push_str(&format!("  (shadows {})", shadows.join(", ")));
+		}
+		println!("{line}");
+	}
+	Ok(())
+}
@@ -0,0 +1,11 @@
+pub mod check;
+pub mod fav;
+pub mod filter;
+pub mod follow;
+pub mod info;
+pub mod list;
+pub mod random;
+pub mod schedule;
+pub mod sync;
+pub mod tag;
+pub mod toggle;
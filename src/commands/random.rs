@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use crate::filter::{Comparison, Filter};
+use crate::state::State;
+use crate::store::ThemeStore;
+use crate::structured_data::structs::Colors;
+use crate::{apply_theme_by_name, Result};
+
+#[derive(Debug, Clone, Default)]
+pub struct RandomOptions {
+	pub dark: bool,
+	pub light: bool,
+	pub min_contrast: Option<f64>,
+	pub tags: Vec<String>,
+	pub favorites: bool,
+	/// A filter expression, see [`Filter`].
+	pub filter: Option<String>,
+	/// How many of the most recent picks are excluded from the draw.
+	pub no_repeat: usize,
+}
+
+pub const DEFAULT_NO_REPEAT: usize = 10;
+
+impl RandomOptions {
+	/// All of the options as a single filter.
+	fn filter(&self, state: &State) -> Result<Filter> {
+		let mut filters = Vec::new();
+		if self.dark {
+			filters.push(Filter::Dark);
+		}
+		if self.light {
+			filters.push(Filter::Light);
+		}
+		if let Some(min) = self.min_contrast {
+			filters.push(Filter::Contrast(Comparison::GreaterOrEqual, min));
+		}
+		filters.extend(self.tags.iter().cloned().map(Filter::Tag));
+		if self.favorites {
+			filters.push(Filter::Favorite);
+		}
+		if let Some(expression) = &self.filter {
+			filters.push(Filter::parse(expression, state)?);
+		}
+		Ok(Filter::All(filters))
+	}
+}
+
+/// Picks one of `candidates`, avoiding the last `no_repeat` picks when there is anything else
+/// left to choose from.
+pub fn choose<'a>(candidates: &'a [String], state: &State, no_repeat: usize) -> Option<&'a String> {
+	let recent = state.last_picks(no_repeat);
+	let fresh: Vec<&String> = candidates.iter().filter(|name| !recent.contains(name)).collect();
+
+	if fresh.is_empty() {
+		let last = state.recent.last();
+		let fallback: Vec<&String> = candidates.iter().filter(|name| Some(*name) != last).collect();
+		return if fallback.is_empty() {
+			candidates.first()
+		} else {
+			Some(fallback[fastrand::usize(..fallback.len())])
+		};
+	}
+
+	Some(fresh[fastrand::usize(..fresh.len())])
+}
+
+/// Draws a theme matching `options` and returns its name, without applying it.
+pub fn pick(options: &RandomOptions, store: &ThemeStore, default_theme: &Colors, state: &State) -> Result<String> {
+	let filter = options.filter(state)?;
+	let candidates: Vec<String> = store
+		.entries()
+		.iter()
+		.filter(|entry| store.load(entry).is_ok() && filter.matches_entry(store, entry, default_theme, state))
+		.map(|entry| entry.name.clone())
+		.collect();
+
+	choose(&candidates, state, options.no_repeat)
+		.cloned()
+		.ok_or_else(|| "No theme matches the given filters".into())
+}
+
+pub fn run(options: &RandomOptions, store: &ThemeStore, config_path: &Path, default_theme: &Colors) -> Result<()> {
+	let name = pick(options, store, default_theme, &State::load()?)?;
+	apply_theme_by_name(store, config_path, default_theme, &name)?;
+
+	let mut state = State::load()?;
+	state.record_pick(&name);
+	state.save()?;
+
+	println!("{name}");
+	Ok(())
+}
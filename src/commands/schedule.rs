@@ -0,0 +1,45 @@
+use chrono::TimeDelta;
+
+use crate::schedule::{active_slot, Clock};
+use crate::settings::Settings;
+use crate::Result;
+
+/// Upper bound on a single sleep, so suspend/resume and clock changes are noticed quickly.
+const MAX_SLEEP_SECONDS: i64 = 60;
+
+/// Applies the scheduled theme through `apply`, then (unless `once`) keeps running and switches
+/// themes as the schedule advances. Only transitions are applied, so manual changes stick until
+/// the next one.
+pub fn run(
+	once: bool,
+	clock: &impl Clock,
+	settings: &Settings,
+	mut apply: impl FnMut(&str) -> Result<()>,
+) -> Result<()> {
+	let mut applied: Option<String> = None;
+
+	loop {
+		let now = clock.now();
+		let slot = active_slot(&settings.schedule, now)?.ok_or_else(|| {
+			let path = Settings::path().unwrap_or_default();
+			format!("No schedule configured in {}", path.display())
+		})?;
+
+		if applied.as_ref() != Some(&slot.theme) {
+			apply(&slot.theme)?;
+			println!(
+				"{} {} (until {})",
+				now.format("%H:%M"),
+				slot.theme,
+				slot.until.format("%H:%M")
+			);
+			applied = Some(slot.theme);
+		}
+
+		if once {
+			return Ok(());
+		}
+
+		clock.sleep_until(slot.until.min(now + TimeDelta::seconds(MAX_SLEEP_SECONDS)));
+	}
+}
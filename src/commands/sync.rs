@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use crate::sync::sync_themes;
+use crate::Result;
+
+pub fn run(themes_path: &Path, dry_run: bool) -> Result<()> {
+	let report = sync_themes(themes_path, dry_run)?;
+	let (installed, updated) = if dry_run {
+		("Would install", "Would update")
+	} else {
+		("Installed", "Updated")
+	};
+
+	for name in &report.installed {
+		println!("{installed}: {name}");
+	}
+	for name in &report.updated {
+		println!("{updated}: {name}");
+	}
+	for name in &report.conflicts {
+		println!("Kept local changes: {name}");
+	}
+	for name in &report.removed {
+		println!("Kept deleted: {name}");
+	}
+
+	println!(
+		"{} installed, {} updated, {} locally modified, {} deleted, {} up to date",
+		report.installed.len(),
+		report.updated.len(),
+		report.conflicts.len(),
+		report.removed.len(),
+		report.unchanged
+	);
+	Ok(())
+}
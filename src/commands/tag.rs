@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+
+use crate::filter::theme_tags;
+use crate::state::State;
+use crate::store::ThemeStore;
+use crate::Result;
+
+pub enum TagAction {
+	Add(String, Vec<String>),
+	Remove(String, Vec<String>),
+	/// All tags in use, or the tags of the named theme.
+	List(Option<String>),
+}
+
+pub fn run(action: &TagAction, store: &ThemeStore) -> Result<()> {
+	let mut state = State::load()?;
+
+	match action {
+		TagAction::Add(name, tags) => {
+			let entry = store.get(name)?;
+			for tag in tags {
+				if !state.add_tag(&entry.name, tag) {
+					println!("'{}' already is tagged '{tag}'", entry.name);
+				}
+			}
+			state.save()
+		},
+		TagAction::Remove(name, tags) => {
+			let name = store.find(name).map_or(name.as_str(), |entry| entry.name.as_str());
+			for tag in tags {
+				if !state.remove_tag(name, tag) {
+					println!("'{name}' is not tagged '{tag}'");
+				}
+			}
+			state.save()
+		},
+		TagAction::List(Some(name)) => {
+			for tag in theme_tags(store.get(name)?, &state) {
+				println!("{tag}");
+			}
+			Ok(())
+		},
+		TagAction::List(None) => {
+			let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+			for entry in store.entries() {
+				for tag in theme_tags(entry, &state) {
+					*counts.entry(tag.to_lowercase()).or_default() += 1;
+				}
+			}
+			for (tag, count) in counts {
+				println!("{tag}  ({count})");
+			}
+			Ok(())
+		},
+	}
+}
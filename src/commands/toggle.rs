@@ -0,0 +1,19 @@
+use std::path::Path;
+
+use crate::pairs::counterpart;
+use crate::state::State;
+use crate::store::ThemeStore;
+use crate::structured_data::structs::Colors;
+use crate::{apply_theme_by_name, Result};
+
+pub fn counterpart_of_current(store: &ThemeStore) -> Result<String> {
+	let current = State::load()?.current.ok_or("No theme has been applied yet")?;
+	counterpart(store.entries(), &current).ok_or_else(|| format!("'{current}' has no light/dark counterpart").into())
+}
+
+pub fn run(store: &ThemeStore, config_path: &Path, default_theme: &Colors) -> Result<()> {
+	let name = counterpart_of_current(store)?;
+	apply_theme_by_name(store, config_path, default_theme, &name)?;
+	println!("{name}");
+	Ok(())
+}
@@ -0,0 +1,314 @@
+//! Long-running daemon that owns theme switching and accepts one-line commands on a Unix socket,
+//! so window-manager keybindings can switch themes without starting the TUI.
+//!
+//! Each request is a single line, `<command> [argument]`; each response is a single line, either
+//! `ok <theme>` or `error <message>`.
+
+use dirs::runtime_dir;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use std::{fs, thread};
+
+use crate::cli::parse_random;
+use crate::commands::{follow, random, schedule, toggle};
+use crate::schedule::SystemClock;
+use crate::settings::Settings;
+use crate::state::State;
+use crate::store::ThemeStore;
+use crate::structured_data::structs::Colors;
+use crate::{apply_theme_by_name, Result};
+
+/// Longest request accepted, in bytes; theme names and `random` filters fit well within it.
+const MAX_REQUEST: u64 = 4096;
+/// How long either side waits on a quiet peer, so a stuck client cannot hold up the daemon.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DaemonOptions {
+	pub schedule: bool,
+	pub follow_system: bool,
+}
+
+const SOCKET_NAME: &str = "alacritty_themes.sock";
+
+/// The daemon's socket, in the runtime directory, or else in a directory of the temporary
+/// directory that only the current user can enter.
+pub fn socket_path() -> Result<PathBuf> {
+	if let Some(dir) = runtime_dir() {
+		return Ok(dir.join(SOCKET_NAME));
+	}
+
+	let uid = current_uid();
+	let dir = std::env::temp_dir().join(format!("alacritty_themes-{uid}"));
+	match fs::DirBuilder::new().mode(0o700).create(&dir) {
+		Err(err) if err.kind() != std::io::ErrorKind::AlreadyExists => {
+			return Err(format!("Could not create {}: {err}", dir.display()).into());
+		},
+		_ => {},
+	}
+	// It may have been created by someone else, to listen in on the requests.
+	let metadata = fs::symlink_metadata(&dir)?;
+	if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+		return Err(format!("{} is not a private directory of the current user", dir.display()).into());
+	}
+	Ok(dir.join(SOCKET_NAME))
+}
+
+fn current_uid() -> u32 {
+	// SAFETY: `getuid` has no preconditions and cannot fail.
+	unsafe { libc::getuid() }
+}
+
+struct Daemon {
+	themes_path: PathBuf,
+	config_path: PathBuf,
+	default_theme: Colors,
+	settings: Settings,
+}
+
+impl Daemon {
+	fn handle(&self, request: &str) -> Result<String> {
+		let request = request.trim();
+		let (command, argument) = request.split_once(' ').unwrap_or((request, ""));
+		let argument = argument.trim();
+
+		match command {
+			"apply" if argument.is_empty() => Err("'apply' requires a theme name".into()),
+			"apply" => self.apply(argument),
+			"next" => self.step(true),
+			"prev" => self.step(false),
+			"random" => self.random(argument),
+			"toggle" => self.toggle(),
+			"toggle-dark-light" => self.toggle_dark_light(),
+			"current" => State::load()?
+				.current
+				.ok_or_else(|| "No theme has been applied yet".into()),
+			other => Err(format!("Unknown command '{other}'").into()),
+		}
+	}
+
+	/// Themes are listed afresh for every request, so the daemon sees themes added after it
+	/// started.
+	fn store(&self) -> Result<ThemeStore> {
+		ThemeStore::open(&self.themes_path)
+	}
+
+	fn apply(&self, name: &str) -> Result<String> {
+		let store = self.store()?;
+		apply_theme_by_name(&store, &self.config_path, &self.default_theme, name)?;
+		Ok(store.get(name)?.name.clone())
+	}
+
+	fn step(&self, forward: bool) -> Result<String> {
+		let name = neighbour(&self.store()?, State::load()?.current.as_deref(), forward)?;
+		self.apply(&name)
+	}
+
+	fn random(&self, arguments: &str) -> Result<String> {
+		let options = parse_random(arguments.split_whitespace().map(String::from))?;
+		let name = random::pick(&options, &self.store()?, &self.default_theme, &State::load()?)?;
+		self.apply(&name)?;
+
+		let mut state = State::load()?;
+		state.record_pick(&name);
+		state.save()?;
+		Ok(name)
+	}
+
+	fn toggle(&self) -> Result<String> {
+		let name = toggle::counterpart_of_current(&self.store()?)?;
+		self.apply(&name)
+	}
+
+	fn toggle_dark_light(&self) -> Result<String> {
+		let appearance = self
+			.settings
+			.appearance
+			.as_ref()
+			.ok_or("No [appearance] light/dark themes configured")?;
+
+		let target = if State::load()?.current.as_deref() == Some(appearance.dark.as_str()) {
+			&appearance.light
+		} else {
+			&appearance.dark
+		};
+		self.apply(target)
+	}
+}
+
+/// The theme after (or before) `current` that loads, skipping broken ones and wrapping around at
+/// most once.
+fn neighbour(store: &ThemeStore, current: Option<&str>, forward: bool) -> Result<String> {
+	let entries = store.entries();
+	let count = entries.len();
+	if count == 0 {
+		return Err("No themes installed".into());
+	}
+
+	// Without a current theme, start just before the first theme, or just after the last.
+	let position = current
+		.and_then(|current| entries.iter().position(|entry| entry.name == current))
+		.unwrap_or(if forward { count - 1 } else { 0 });
+	(1..=count)
+		.map(|step| {
+			if forward {
+				(position + step) % count
+			} else {
+				(position + count - step) % count
+			}
+		})
+		.map(|index| &entries[index])
+		.find(|entry| store.load(entry).is_ok())
+		.map(|entry| entry.name.clone())
+		.ok_or_else(|| "None of the themes can be loaded".into())
+}
+
+fn lock(daemon: &Mutex<Daemon>) -> Result<MutexGuard<'_, Daemon>> {
+	daemon.lock().map_err(|_| "Daemon state is poisoned".into())
+}
+
+/// Runs the daemon until the process is terminated.
+pub fn serve(
+	options: DaemonOptions,
+	settings: Settings,
+	themes_path: PathBuf,
+	config_path: PathBuf,
+	default_theme: Colors,
+) -> Result<()> {
+	let path = socket_path()?;
+	if UnixStream::connect(&path).is_ok() {
+		return Err(format!("A daemon is already listening on {}", path.display()).into());
+	}
+	// A socket of our own left at the path is from a daemon that did not shut down cleanly.
+	if let Ok(metadata) = fs::symlink_metadata(&path) {
+		if !metadata.file_type().is_socket() || metadata.uid() != current_uid() {
+			return Err(format!(
+				"{} is not a socket of the current user, not replacing it",
+				path.display()
+			)
+			.into());
+		}
+		fs::remove_file(&path)?;
+	}
+	let listener = UnixListener::bind(&path)?;
+
+	let daemon = Arc::new(Mutex::new(Daemon {
+		themes_path,
+		config_path,
+		default_theme,
+		settings: settings.clone(),
+	}));
+
+	if options.schedule {
+		let daemon = Arc::clone(&daemon);
+		let settings = settings.clone();
+		thread::spawn(move || {
+			let apply = |name: &str| lock(&daemon)?.apply(name).map(drop);
+			if let Err(err) = schedule::run(false, &SystemClock, &settings, apply) {
+				eprintln!("Schedule stopped: {err}");
+			}
+		});
+	}
+
+	if options.follow_system {
+		let daemon = Arc::clone(&daemon);
+		thread::spawn(move || {
+			let apply = |name: &str| lock(&daemon)?.apply(name).map(drop);
+			if let Err(err) = follow::run(&settings, apply) {
+				eprintln!("Following the system appearance stopped: {err}");
+			}
+		});
+	}
+
+	println!("Listening on {}", path.display());
+	for stream in listener.incoming() {
+		let Ok(stream) = stream else { continue };
+		// Each client gets its own thread; requests still run one at a time under the lock.
+		let daemon = Arc::clone(&daemon);
+		thread::spawn(move || {
+			if let Err(err) = respond(&daemon, &stream) {
+				eprintln!("Failed to answer request: {err}");
+			}
+		});
+	}
+
+	Ok(())
+}
+
+fn respond(daemon: &Mutex<Daemon>, mut stream: &UnixStream) -> Result<()> {
+	stream.set_read_timeout(Some(TIMEOUT))?;
+	stream.set_write_timeout(Some(TIMEOUT))?;
+
+	let mut request = String::new();
+	BufReader::new(stream.take(MAX_REQUEST)).read_line(&mut request)?;
+	if !request.ends_with('\n') && request.len() as u64 >= MAX_REQUEST {
+		writeln!(stream, "error Request is longer than {MAX_REQUEST} bytes")?;
+		return Ok(());
+	}
+	if request.trim().is_empty() {
+		return Ok(());
+	}
+
+	match lock(daemon).and_then(|daemon| daemon.handle(&request)) {
+		Ok(theme) => {
+			println!("{}: {theme}", request.trim());
+			writeln!(stream, "ok {theme}")?;
+		},
+		Err(err) => {
+			let message = err.to_string().replace('\n', " ");
+			eprintln!("{}: {message}", request.trim());
+			writeln!(stream, "error {message}")?;
+		},
+	}
+
+	Ok(())
+}
+
+/// Sends one request to a running daemon and returns the theme it reports.
+pub fn send(request: &str) -> Result<String> {
+	let path = socket_path()?;
+	let mut stream =
+		UnixStream::connect(&path).map_err(|err| format!("Could not reach the daemon at {}: {err}", path.display()))?;
+	stream.set_read_timeout(Some(TIMEOUT))?;
+	stream.set_write_timeout(Some(TIMEOUT))?;
+	writeln!(stream, "{request}")?;
+
+	let mut response = String::new();
+	BufReader::new(stream)
+		.read_line(&mut response)
+		.map_err(|err| format!("The daemon did not answer: {err}"))?;
+
+	match response.trim_end().split_once(' ') {
+		Some(("ok", theme)) => Ok(theme.to_string()),
+		Some(("error", message)) => Err(message.into()),
+		_ => Err(format!("Unexpected response from the daemon: '{}'", response.trim_end()).into()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::store::open_test_store;
+
+	const THEME: &str = "[colors.primary]\nbackground = \"#000000\"\nforeground = \"#ffffff\"\n";
+
+	#[test]
+	fn steps_over_broken_neighbours() {
+		let store = open_test_store(&[
+			("Zzz 1", THEME),
+			("Zzz 2", "[colors.primary]\nbackground = \"oops\"\n"),
+			("Zzz 3", THEME),
+		]);
+
+		assert_eq!(neighbour(&store, Some("Zzz 1"), true).unwrap(), "Zzz 3");
+		assert_eq!(neighbour(&store, Some("Zzz 3"), false).unwrap(), "Zzz 1");
+		// Past the end, it wraps around to the first theme.
+		let first = &store.entries()[0].name;
+		assert_eq!(neighbour(&store, Some("Zzz 3"), true).unwrap(), *first);
+		assert_eq!(neighbour(&store, Some(first), false).unwrap(), "Zzz 3");
+	}
+}
@@ -0,0 +1,400 @@
+//! Families of related themes for the grouped view of the theme list. Themes in a subdirectory of
+//! a themes directory form a family named after it; all other themes are grouped by the first
+//! word of their name (`Catppuccin-Mocha`, `Dkeg - panels`, `Embers.dark`), and families whose
+//! names share a long prefix (`Atelierdune`, `Atelierforest`) are merged.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::state::State;
+use crate::store::ThemeEntry;
+use crate::utils::names::{display_name, natural_cmp};
+
+const SEPARATORS: [char; 4] = ['.', '-', '_', ' '];
+/// The number of leading characters two family names must share to be merged.
+const SHARED_PREFIX: usize = 7;
+
+pub struct Family {
+	pub name: String,
+	/// Indices into the theme entries, in list order.
+	pub members: Vec<usize>,
+}
+
+enum Item {
+	Theme(usize),
+	Family(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Row {
+	Family(usize),
+	Theme { entry: usize, nested: bool },
+}
+
+/// Where favorite themes show up in the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FavoritesView {
+	/// In their usual place.
+	Mixed,
+	/// Additionally pinned to the top of the list.
+	First,
+	/// Nothing but the favorites.
+	Only,
+}
+
+impl FavoritesView {
+	fn next(self) -> Self {
+		match self {
+			Self::Mixed => Self::First,
+			Self::First => Self::Only,
+			Self::Only => Self::Mixed,
+		}
+	}
+}
+
+/// The rows of the theme list, either grouped into collapsible families or flat.
+pub struct ThemeTree {
+	families: Vec<Family>,
+	items: Vec<Item>,
+	favorites: Vec<bool>,
+	favorites_view: FavoritesView,
+	/// Themes matching the current filter.
+	visible: Vec<bool>,
+	expanded: BTreeSet<String>,
+	grouped: bool,
+	rows: Vec<Row>,
+}
+
+impl ThemeTree {
+	/// Groups `entries`, with every family collapsed.
+	pub fn new(entries: &[ThemeEntry], state: &State) -> Self {
+		let (families, items) = group(entries);
+		let mut tree = Self {
+			families,
+			items,
+			favorites: entries.iter().map(|entry| state.is_favorite(&entry.name)).collect(),
+			favorites_view: FavoritesView::Mixed,
+			visible: vec![true; entries.len()],
+			expanded: BTreeSet::new(),
+			grouped: true,
+			rows: Vec::new(),
+		};
+		tree.rebuild();
+		tree
+	}
+
+	/// Regroups the tree for a new list of `entries`, keeping the view as it was, and returns the
+	/// row of the theme named `selected`, if it is still shown.
+	pub fn replace_entries(
+		&mut self,
+		entries: &[ThemeEntry],
+		state: &State,
+		visible: Vec<bool>,
+		selected: &str,
+	) -> Option<usize> {
+		let (families, items) = group(entries);
+		self.families = families;
+		self.items = items;
+		self.favorites = entries.iter().map(|entry| state.is_favorite(&entry.name)).collect();
+		self.visible = visible;
+		self.rebuild();
+		entries
+			.iter()
+			.position(|entry| entry.name == selected)
+			.filter(|&entry| self.visible[entry])
+			.map(|entry| self.locate(entry))
+	}
+
+	fn rebuild(&mut self) {
+		self.rows.clear();
+
+		let visible = |entry: &usize| self.visible[*entry];
+		let favorites: Vec<usize> = (0..self.favorites.len())
+			.filter(|&entry| self.favorites[entry])
+			.filter(visible)
+			.collect();
+
+		// Without favorites, a favorites view would be empty.
+		if favorites.is_empty() {
+			self.favorites_view = FavoritesView::Mixed;
+		}
+		if self.favorites_view != FavoritesView::Mixed {
+			self.rows
+				.extend(favorites.into_iter().map(|entry| Row::Theme { entry, nested: false }));
+			if self.favorites_view == FavoritesView::Only {
+				return;
+			}
+		}
+
+		if !self.grouped {
+			let entries = (0..self.visible.len()).filter(visible);
+			self.rows
+				.extend(entries.map(|entry| Row::Theme { entry, nested: false }));
+			return;
+		}
+
+		for item in &self.items {
+			match *item {
+				Item::Theme(entry) if self.visible[entry] => self.rows.push(Row::Theme { entry, nested: false }),
+				Item::Theme(_) => {},
+				Item::Family(index) => {
+					let family = &self.families[index];
+					let mut members = family.members.iter().copied().filter(visible).peekable();
+					if members.peek().is_none() {
+						continue;
+					}
+					self.rows.push(Row::Family(index));
+					if self.expanded.contains(&family.name) {
+						self.rows
+							.extend(members.map(|entry| Row::Theme { entry, nested: true }));
+					}
+				},
+			}
+		}
+	}
+
+	fn visible_members(&self, family: usize) -> impl Iterator<Item = usize> + '_ {
+		self.families[family]
+			.members
+			.iter()
+			.copied()
+			.filter(|&entry| self.visible[entry])
+	}
+
+	pub fn is_visible(&self, entry: usize) -> bool {
+		self.visible[entry]
+	}
+
+	/// Shows only the themes marked in `visible`. Returns the row the selection at `row` ends up
+	/// in, or `None`, leaving everything as it was, if no theme is marked.
+	pub fn set_visible(&mut self, visible: Vec<bool>, row: usize) -> Option<usize> {
+		if !visible.contains(&true) {
+			return None;
+		}
+		let selected = self.rows[row];
+		self.visible = visible;
+		self.rebuild();
+		Some(self.reselect(selected))
+	}
+
+	pub fn rows(&self) -> &[Row] {
+		&self.rows
+	}
+
+	/// The theme shown for `row`. A family row stands for its first member.
+	pub fn entry_at(&self, row: usize) -> usize {
+		match self.rows[row] {
+			Row::Family(index) => self.visible_members(index).next().unwrap_or_default(),
+			Row::Theme { entry, .. } => entry,
+		}
+	}
+
+	fn row_of(&self, target: Row) -> usize {
+		self.rows.iter().position(|row| *row == target).unwrap_or(0)
+	}
+
+	/// The first row showing `entry`, or else the row of its collapsed family.
+	fn locate(&self, entry: usize) -> usize {
+		let shown = self
+			.rows
+			.iter()
+			.position(|row| matches!(*row, Row::Theme { entry: shown, .. } if shown == entry));
+		match (shown, self.family_of(entry)) {
+			(Some(row), _) => row,
+			(None, Some(family)) => self.row_of(Row::Family(family)),
+			(None, None) => 0,
+		}
+	}
+
+	/// The row that `previous` ended up in after a change of view.
+	fn reselect(&self, previous: Row) -> usize {
+		match previous {
+			Row::Family(index) => match self.rows.iter().position(|row| *row == previous) {
+				Some(row) => row,
+				None => self.visible_members(index).next().map_or(0, |entry| self.locate(entry)),
+			},
+			Row::Theme { entry, .. } => self.locate(entry),
+		}
+	}
+
+	/// Expands or collapses the family at `row`. Returns false if `row` is not a family.
+	pub fn toggle(&mut self, row: usize) -> bool {
+		let Row::Family(index) = self.rows[row] else {
+			return false;
+		};
+		let name = &self.families[index].name;
+		if !self.expanded.remove(name) {
+			self.expanded.insert(name.clone());
+		}
+		self.rebuild();
+		true
+	}
+
+	pub fn expand(&mut self, row: usize) {
+		if let Row::Family(index) = self.rows[row] {
+			self.expanded.insert(self.families[index].name.clone());
+			self.rebuild();
+		}
+	}
+
+	/// Collapses the family at `row`, or the family containing it, and returns the row of the
+	/// family.
+	pub fn collapse(&mut self, row: usize) -> usize {
+		let family = match self.rows[row] {
+			Row::Family(index) => index,
+			Row::Theme { entry, nested: true } => match self.family_of(entry) {
+				Some(index) => index,
+				None => return row,
+			},
+			Row::Theme { nested: false, .. } => return row,
+		};
+		self.expanded.remove(&self.families[family].name);
+		self.rebuild();
+		self.row_of(Row::Family(family))
+	}
+
+	fn family_of(&self, entry: usize) -> Option<usize> {
+		self.families.iter().position(|family| family.members.contains(&entry))
+	}
+
+	/// Expands whatever is needed to show `entry` and returns its row.
+	pub fn reveal(&mut self, entry: usize) -> usize {
+		if self.favorites_view == FavoritesView::Only && !self.favorites[entry] {
+			self.favorites_view = FavoritesView::Mixed;
+		}
+		if let Some(index) = self.family_of(entry).filter(|_| self.grouped) {
+			self.expanded.insert(self.families[index].name.clone());
+		}
+		self.rebuild();
+		self.locate(entry)
+	}
+
+	/// Switches between the grouped and the flat view. Returns the row the selection at `row` ends
+	/// up in.
+	pub fn toggle_grouped(&mut self, row: usize) -> usize {
+		let selected = self.rows[row];
+		self.grouped = !self.grouped;
+		self.rebuild();
+		self.reselect(selected)
+	}
+
+	/// Moves on to the next [`FavoritesView`]. Returns the row the selection at `row` ends up in.
+	pub fn cycle_favorites_view(&mut self, row: usize) -> usize {
+		let selected = self.rows[row];
+		self.favorites_view = self.favorites_view.next();
+		self.rebuild();
+		self.reselect(selected)
+	}
+
+	pub fn favorites_view(&self) -> FavoritesView {
+		self.favorites_view
+	}
+
+	/// Marks `entry` as a favorite or not. Returns the row the selection at `row` ends up in.
+	pub fn set_favorite(&mut self, entry: usize, favorite: bool, row: usize) -> usize {
+		let selected = self.rows[row];
+		self.favorites[entry] = favorite;
+		self.rebuild();
+		self.reselect(selected)
+	}
+
+	/// The list labels for all rows, given the label of each theme.
+	pub fn labels(&self, theme_names: &[String]) -> Vec<String> {
+		self.rows
+			.iter()
+			.map(|row| match *row {
+				Row::Family(index) => {
+					let family = &self.families[index];
+					let marker = if self.expanded.contains(&family.name) {
+						'▾'
+					} else {
+						'▸'
+					};
+					format!("{marker} {} ({})", family.name, self.visible_members(index).count())
+				},
+				Row::Theme { entry, nested } => {
+					let star = if self.favorites[entry] { '★' } else { ' ' };
+					let indent = if nested { "  " } else { "" };
+					format!("{indent}{star} {}", theme_names[entry])
+				},
+			})
+			.collect()
+	}
+}
+
+/// The name of the family `entry` belongs to, and whether it is a category (a subdirectory).
+fn family_key(entry: &ThemeEntry) -> (&str, bool) {
+	if let Some(category) = entry.category() {
+		return (category, true);
+	}
+	let name = entry.name.as_str();
+	let key = match name.split_once(" - ") {
+		Some((author, _)) => author.trim(),
+		None => name.split(SEPARATORS).next().unwrap_or(name),
+	};
+	(key, false)
+}
+
+fn group(entries: &[ThemeEntry]) -> (Vec<Family>, Vec<Item>) {
+	// Families by lowercased name, each with the name to show and its members.
+	let mut keyed: BTreeMap<String, (String, Vec<usize>)> = BTreeMap::new();
+	let mut categories = BTreeSet::new();
+	for (index, entry) in entries.iter().enumerate() {
+		let (key, category) = family_key(entry);
+		let folded = key.to_lowercase();
+		if category {
+			categories.insert(folded.clone());
+		}
+		keyed
+			.entry(folded)
+			.or_insert_with(|| (key.to_string(), Vec::new()))
+			.1
+			.push(index);
+	}
+
+	// Merge families whose names share a long prefix under that common prefix.
+	let mut merged: BTreeMap<String, (String, Vec<usize>)> = BTreeMap::new();
+	for (folded, (key, members)) in keyed {
+		let prefix: String = folded.chars().take(SHARED_PREFIX).collect();
+		let mergeable = !categories.contains(&folded) && prefix.chars().count() == SHARED_PREFIX;
+		let slot = if mergeable { format!("~{prefix}") } else { folded };
+		match merged.get_mut(&slot) {
+			Some((name, existing)) => {
+				*name = common_prefix(name, &key);
+				existing.extend(members);
+				existing.sort_unstable();
+			},
+			None => {
+				merged.insert(slot, (key, members));
+			},
+		}
+	}
+
+	let mut families = Vec::new();
+	let mut items = Vec::new();
+	for (slot, (name, members)) in merged {
+		let category = categories.contains(&slot);
+		if category || (members.len() > 1 && name.chars().count() > 1) {
+			let name = if category { name } else { display_name(&name) };
+			items.push(Item::Family(families.len()));
+			families.push(Family { name, members });
+		} else {
+			items.extend(members.into_iter().map(Item::Theme));
+		}
+	}
+
+	let title = |item: &Item| match item {
+		Item::Theme(entry) => entries[*entry].display_name(),
+		Item::Family(index) => families[*index].name.clone(),
+	};
+	items.sort_by(|a, b| natural_cmp(&title(a), &title(b)));
+
+	(families, items)
+}
+
+fn common_prefix(a: &str, b: &str) -> String {
+	a.chars()
+		.zip(b.chars())
+		.take_while(|(x, y)| x.eq_ignore_ascii_case(y))
+		.map(|(x, _)| x)
+		.collect()
+}
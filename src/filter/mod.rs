@@ -0,0 +1,455 @@
+//! Filter expressions over themes, such as `dark && contrast>7 && tag:retro`.
+//!
+//! Terms are `dark`, `light`, `favorite`, `tag:TAG`, `name:TEXT` and `contrast` compared with
+//! `<`, `<=`, `=`, `>=` or `>` to a number. They combine with `!`, `&&`, `||` and parentheses,
+//! and `@NAME` stands for the saved filter of that name.
+
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::state::State;
+use crate::store::{ThemeEntry, ThemeStore};
+use crate::structured_data::structs::{merge_colors, Colors, Variant};
+use crate::utils::color::Stats;
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+	Less,
+	LessOrEqual,
+	Equal,
+	GreaterOrEqual,
+	Greater,
+}
+
+impl Comparison {
+	fn holds(self, value: f64, bound: f64) -> bool {
+		match self {
+			Self::Less => value < bound,
+			Self::LessOrEqual => value <= bound,
+			// Contrast ratios are usually quoted with one decimal.
+			Self::Equal => (value - bound).abs() < 0.05,
+			Self::GreaterOrEqual => value >= bound,
+			Self::Greater => value > bound,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+	Dark,
+	Light,
+	Favorite,
+	Tag(String),
+	Name(String),
+	Contrast(Comparison, f64),
+	Not(Box<Filter>),
+	/// Matches when all filters match, so an empty list matches everything.
+	All(Vec<Filter>),
+	Any(Vec<Filter>),
+}
+
+/// The facts about a theme that filters look at.
+pub struct Subject {
+	pub name: String,
+	pub tags: Vec<String>,
+	pub favorite: bool,
+	variant: Option<Variant>,
+	stats: Option<Stats>,
+}
+
+impl Subject {
+	/// `stats` are those of the theme's colors as they would be applied, i.e. merged with the base
+	/// theme.
+	pub fn new(entry: &ThemeEntry, stats: Option<Stats>, state: &State) -> Self {
+		let metadata = entry.metadata().unwrap_or_default();
+		Self {
+			name: entry.name.clone(),
+			tags: with_user_tags(metadata.tags, &entry.name, state),
+			favorite: state.is_favorite(&entry.name),
+			variant: metadata.variant,
+			stats,
+		}
+	}
+
+	/// A theme is dark when its metadata says so or, failing that, when its background is darker
+	/// than its foreground.
+	pub fn is_dark(&self) -> Option<bool> {
+		if let Some(variant) = self.variant {
+			return Some(variant == Variant::Dark);
+		}
+		self.stats.map(|stats| stats.dark)
+	}
+
+	pub fn contrast(&self) -> Option<f64> {
+		self.stats.map(|stats| stats.contrast)
+	}
+}
+
+/// The tags of a theme: those from its metadata followed by the user's own.
+pub fn theme_tags(entry: &ThemeEntry, state: &State) -> Vec<String> {
+	let tags = entry.metadata().map(|metadata| metadata.tags).unwrap_or_default();
+	with_user_tags(tags, &entry.name, state)
+}
+
+fn with_user_tags(mut tags: Vec<String>, name: &str, state: &State) -> Vec<String> {
+	for tag in state.tags.get(name).into_iter().flatten() {
+		if !tags.iter().any(|known| known.eq_ignore_ascii_case(tag)) {
+			tags.push(tag.clone());
+		}
+	}
+	tags
+}
+
+impl Filter {
+	/// Parses `expression`, resolving `@NAME` through the saved filters in `state`.
+	pub fn parse(expression: &str, state: &State) -> Result<Self> {
+		parse_with(expression, &state.filters, &mut Vec::new())
+	}
+
+	pub fn matches(&self, subject: &Subject) -> bool {
+		match self {
+			Self::Dark => subject.is_dark() == Some(true),
+			Self::Light => subject.is_dark() == Some(false),
+			Self::Favorite => subject.favorite,
+			Self::Tag(tag) => subject.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+			Self::Name(text) => subject.name.to_lowercase().contains(&text.to_lowercase()),
+			Self::Contrast(comparison, bound) => subject
+				.contrast()
+				.is_some_and(|contrast| comparison.holds(contrast, *bound)),
+			Self::Not(filter) => !filter.matches(subject),
+			Self::All(filters) => filters.iter().all(|filter| filter.matches(subject)),
+			Self::Any(filters) => filters.iter().any(|filter| filter.matches(subject)),
+		}
+	}
+
+	pub fn matches_everything(&self) -> bool {
+		matches!(self, Self::All(filters) if filters.is_empty())
+	}
+
+	/// Whether the theme of `entry` matches. Themes that fail to load never match.
+	pub fn matches_entry(&self, store: &ThemeStore, entry: &ThemeEntry, default_theme: &Colors, state: &State) -> bool {
+		if self.matches_everything() {
+			return true;
+		}
+		if let Some(stats) = entry.stats() {
+			return self.matches(&Subject::new(entry, Some(*stats), state));
+		}
+		store.load(entry).is_ok_and(|theme| {
+			self.matches(&Subject::new(
+				entry,
+				Stats::of(&merge_colors(default_theme, &theme)),
+				state,
+			))
+		})
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+	Open,
+	Close,
+	Not,
+	And,
+	Or,
+	Compare(Comparison),
+	Word(String),
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+	let mut tokens = Vec::new();
+	let mut chars = expression.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		let token = match c {
+			c if c.is_whitespace() => continue,
+			'(' => Token::Open,
+			')' => Token::Close,
+			'!' => Token::Not,
+			'&' | '|' if chars.next_if_eq(&c).is_none() => {
+				return Err(format!("Expected '{c}{c}' in filter '{expression}'").into());
+			},
+			'&' => Token::And,
+			'|' => Token::Or,
+			'<' if chars.next_if_eq(&'=').is_some() => Token::Compare(Comparison::LessOrEqual),
+			'<' => Token::Compare(Comparison::Less),
+			'>' if chars.next_if_eq(&'=').is_some() => Token::Compare(Comparison::GreaterOrEqual),
+			'>' => Token::Compare(Comparison::Greater),
+			'=' => {
+				chars.next_if_eq(&'=');
+				Token::Compare(Comparison::Equal)
+			},
+			c => Token::Word(read_word(c, &mut chars)),
+		};
+		tokens.push(token);
+	}
+
+	Ok(tokens)
+}
+
+fn read_word(first: char, chars: &mut Peekable<Chars<'_>>) -> String {
+	let mut word = first.to_string();
+	while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"()!&|<>=".contains(*c)) {
+		word.push(c);
+	}
+	word
+}
+
+fn parse_with(expression: &str, saved: &BTreeMap<String, String>, expanding: &mut Vec<String>) -> Result<Filter> {
+	let mut parser = Parser {
+		expression,
+		tokens: tokenize(expression)?,
+		position: 0,
+		saved,
+		expanding,
+	};
+	if parser.tokens.is_empty() {
+		return Ok(Filter::All(Vec::new()));
+	}
+
+	let filter = parser.parse_or()?;
+	match parser.tokens.get(parser.position) {
+		Some(token) => Err(format!("Unexpected {} in filter '{expression}'", describe(token)).into()),
+		None => Ok(filter),
+	}
+}
+
+fn describe(token: &Token) -> String {
+	match token {
+		Token::Open => "'('".to_string(),
+		Token::Close => "')'".to_string(),
+		Token::Not => "'!'".to_string(),
+		Token::And => "'&&'".to_string(),
+		Token::Or => "'||'".to_string(),
+		Token::Compare(_) => "comparison".to_string(),
+		Token::Word(word) => format!("'{word}'"),
+	}
+}
+
+struct Parser<'a> {
+	expression: &'a str,
+	tokens: Vec<Token>,
+	position: usize,
+	saved: &'a BTreeMap<String, String>,
+	/// Saved filters being expanded, to detect filters that refer to themselves.
+	expanding: &'a mut Vec<String>,
+}
+
+impl Parser<'_> {
+	fn next(&mut self) -> Option<Token> {
+		let token = self.tokens.get(self.position).cloned();
+		self.position += 1;
+		token
+	}
+
+	fn next_if(&mut self, expected: &Token) -> bool {
+		let matched = self.tokens.get(self.position) == Some(expected);
+		if matched {
+			self.position += 1;
+		}
+		matched
+	}
+
+	fn parse_or(&mut self) -> Result<Filter> {
+		let mut filters = vec![self.parse_and()?];
+		while self.next_if(&Token::Or) {
+			filters.push(self.parse_and()?);
+		}
+		Ok(if filters.len() == 1 {
+			filters.remove(0)
+		} else {
+			Filter::Any(filters)
+		})
+	}
+
+	fn parse_and(&mut self) -> Result<Filter> {
+		let mut filters = vec![self.parse_unary()?];
+		while self.next_if(&Token::And) {
+			filters.push(self.parse_unary()?);
+		}
+		Ok(if filters.len() == 1 {
+			filters.remove(0)
+		} else {
+			Filter::All(filters)
+		})
+	}
+
+	fn parse_unary(&mut self) -> Result<Filter> {
+		let expression = self.expression;
+		match self.next() {
+			Some(Token::Not) => Ok(Filter::Not(Box::new(self.parse_unary()?))),
+			Some(Token::Open) => {
+				let filter = self.parse_or()?;
+				if !self.next_if(&Token::Close) {
+					return Err(format!("Missing ')' in filter '{expression}'").into());
+				}
+				Ok(filter)
+			},
+			Some(Token::Word(word)) => self.parse_term(&word),
+			Some(token) => Err(format!("Unexpected {} in filter '{expression}'", describe(&token)).into()),
+			None => Err(format!("Unexpected end of filter '{expression}'").into()),
+		}
+	}
+
+	fn parse_term(&mut self, word: &str) -> Result<Filter> {
+		let expression = self.expression;
+		if let Some(tag) = word.strip_prefix("tag:").filter(|tag| !tag.is_empty()) {
+			return Ok(Filter::Tag(tag.to_string()));
+		}
+		if let Some(text) = word.strip_prefix("name:").filter(|text| !text.is_empty()) {
+			return Ok(Filter::Name(text.to_string()));
+		}
+		if let Some(name) = word.strip_prefix('@') {
+			return self.expand(name);
+		}
+
+		match word.to_lowercase().as_str() {
+			"dark" => Ok(Filter::Dark),
+			"light" => Ok(Filter::Light),
+			"favorite" | "fav" => Ok(Filter::Favorite),
+			"contrast" => {
+				let (Some(Token::Compare(comparison)), Some(Token::Word(bound))) = (self.next(), self.next()) else {
+					return Err(format!("Expected a comparison such as 'contrast>7' in filter '{expression}'").into());
+				};
+				let bound = bound
+					.parse()
+					.map_err(|_| format!("Invalid contrast '{bound}' in filter '{expression}'"))?;
+				Ok(Filter::Contrast(comparison, bound))
+			},
+			_ => Err(format!("Unknown term '{word}' in filter '{expression}'").into()),
+		}
+	}
+
+	fn expand(&mut self, name: &str) -> Result<Filter> {
+		let saved = self
+			.saved
+			.get(name)
+			.ok_or_else(|| format!("Unknown saved filter '{name}'"))?;
+		if self.expanding.iter().any(|expanding| expanding == name) {
+			return Err(format!("Saved filter '{name}' refers to itself").into());
+		}
+
+		self.expanding.push(name.to_string());
+		let filter = parse_with(saved, self.saved, self.expanding);
+		self.expanding.pop();
+		filter
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(expression: &str) -> Result<Filter> {
+		Filter::parse(expression, &State::default())
+	}
+
+	fn parse_saved(expression: &str, saved: &[(&str, &str)]) -> Result<Filter> {
+		let state = State {
+			filters: saved
+				.iter()
+				.map(|(name, filter)| (name.to_string(), filter.to_string()))
+				.collect(),
+			..State::default()
+		};
+		Filter::parse(expression, &state)
+	}
+
+	fn error(result: Result<Filter>) -> String {
+		result.unwrap_err().to_string()
+	}
+
+	fn tag(tag: &str) -> Filter {
+		Filter::Tag(tag.to_string())
+	}
+
+	#[test]
+	fn and_binds_tighter_than_or() {
+		assert_eq!(
+			parse("dark || light && fav").unwrap(),
+			Filter::Any(vec![Filter::Dark, Filter::All(vec![Filter::Light, Filter::Favorite])])
+		);
+		assert_eq!(
+			parse("dark && light || fav").unwrap(),
+			Filter::Any(vec![Filter::All(vec![Filter::Dark, Filter::Light]), Filter::Favorite])
+		);
+	}
+
+	#[test]
+	fn parentheses_group() {
+		assert_eq!(
+			parse("(dark || light) && tag:retro").unwrap(),
+			Filter::All(vec![Filter::Any(vec![Filter::Dark, Filter::Light]), tag("retro")])
+		);
+	}
+
+	#[test]
+	fn not_applies_to_the_next_term() {
+		assert_eq!(
+			parse("!dark && fav").unwrap(),
+			Filter::All(vec![Filter::Not(Box::new(Filter::Dark)), Filter::Favorite])
+		);
+		assert_eq!(
+			parse("!!(dark || fav)").unwrap(),
+			Filter::Not(Box::new(Filter::Not(Box::new(Filter::Any(vec![
+				Filter::Dark,
+				Filter::Favorite
+			])))))
+		);
+	}
+
+	#[test]
+	fn parses_terms() {
+		assert_eq!(parse("").unwrap(), Filter::All(Vec::new()));
+		assert_eq!(parse("name:Night").unwrap(), Filter::Name("Night".to_string()));
+		assert_eq!(
+			parse("contrast>=7.5").unwrap(),
+			Filter::Contrast(Comparison::GreaterOrEqual, 7.5)
+		);
+		assert_eq!(
+			parse("contrast == 4").unwrap(),
+			Filter::Contrast(Comparison::Equal, 4.0)
+		);
+	}
+
+	#[test]
+	fn expands_saved_filters() {
+		let saved = [("retro", "tag:retro || tag:crt"), ("night", "dark && @retro")];
+		assert_eq!(
+			parse_saved("@night && !fav", &saved).unwrap(),
+			Filter::All(vec![
+				Filter::All(vec![Filter::Dark, Filter::Any(vec![tag("retro"), tag("crt")])]),
+				Filter::Not(Box::new(Filter::Favorite)),
+			])
+		);
+		// Using the same saved filter twice is not a cycle.
+		assert!(parse_saved("@retro && @retro", &saved).is_ok());
+	}
+
+	#[test]
+	fn reports_filters_that_refer_to_themselves() {
+		let saved = [("a", "dark && @b"), ("b", "fav || @a"), ("c", "@c")];
+		assert_eq!(error(parse_saved("@a", &saved)), "Saved filter 'a' refers to itself");
+		assert_eq!(error(parse_saved("@c", &saved)), "Saved filter 'c' refers to itself");
+		assert_eq!(error(parse_saved("@d", &saved)), "Unknown saved filter 'd'");
+	}
+
+	#[test]
+	fn reports_bad_input() {
+		assert_eq!(error(parse("dark & light")), "Expected '&&' in filter 'dark & light'");
+		assert_eq!(error(parse("(dark || light")), "Missing ')' in filter '(dark || light'");
+		assert_eq!(error(parse("dark light")), "Unexpected 'light' in filter 'dark light'");
+		assert_eq!(error(parse("dark &&")), "Unexpected end of filter 'dark &&'");
+		assert_eq!(error(parse("|| dark")), "Unexpected '||' in filter '|| dark'");
+		assert_eq!(error(parse("shiny")), "Unknown term 'shiny' in filter 'shiny'");
+		assert_eq!(
+			error(parse("contrast")),
+			"Expected a comparison such as 'contrast>7' in filter 'contrast'"
+		);
+		assert_eq!(
+			error(parse("contrast>high")),
+			"Invalid contrast 'high' in filter 'contrast>high'"
+		);
+	}
+}
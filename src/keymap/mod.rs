@@ -0,0 +1,414 @@
+//! Key bindings of the theme picker. A preset (`default`, `vim` or `emacs`) binds every action,
+//! and the `[keymap]` section of `alacritty_themes.toml` can rebind single actions:
+//!
+//! ```toml
+//! [keymap]
+//! preset = "vim"
+//! group = "o"
+//! quit = ["q", "Ctrl-c"]
+//! ```
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+
+use crate::settings::{KeyList, KeymapSettings};
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+	Up,
+	Down,
+	PageUp,
+	PageDown,
+	First,
+	Last,
+	Apply,
+	Quit,
+	Search,
+	Filter,
+	Favorite,
+	FavoritesView,
+	TogglePair,
+	Compare,
+	Random,
+	Expand,
+	Collapse,
+	Group,
+	Scene,
+}
+
+impl Action {
+	const ALL: [Self; 19] = [
+		Self::Up,
+		Self::Down,
+		Self::PageUp,
+		Self::PageDown,
+		Self::First,
+		Self::Last,
+		Self::Apply,
+		Self::Quit,
+		Self::Search,
+		Self::Filter,
+		Self::Favorite,
+		Self::FavoritesView,
+		Self::TogglePair,
+		Self::Compare,
+		Self::Random,
+		Self::Expand,
+		Self::Collapse,
+		Self::Group,
+		Self::Scene,
+	];
+
+	/// The name of the action in the `[keymap]` section.
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Up => "up",
+			Self::Down => "down",
+			Self::PageUp => "page-up",
+			Self::PageDown => "page-down",
+			Self::First => "first",
+			Self::Last => "last",
+			Self::Apply => "apply",
+			Self::Quit => "quit",
+			Self::Search => "search",
+			Self::Filter => "filter",
+			Self::Favorite => "favorite",
+			Self::FavoritesView => "favorites-view",
+			Self::TogglePair => "toggle-pair",
+			Self::Compare => "compare",
+			Self::Random => "random",
+			Self::Expand => "expand",
+			Self::Collapse => "collapse",
+			Self::Group => "group",
+			Self::Scene => "scene",
+		}
+	}
+}
+
+/// What the keymap bar shows, each entry with the actions whose first key it lists.
+const HINTS: [(&str, &[Action]); 15] = [
+	("Exit", &[Action::Quit]),
+	("Select", &[Action::Apply]),
+	("Navigate", &[Action::Up, Action::Down]),
+	("Page", &[Action::PageUp, Action::PageDown]),
+	("First/Last", &[Action::First, Action::Last]),
+	("Search", &[Action::Search]),
+	("Filter", &[Action::Filter]),
+	("Random", &[Action::Random]),
+	("Light/Dark", &[Action::TogglePair]),
+	("Compare", &[Action::Compare]),
+	("Scene", &[Action::Scene]),
+	("Fold", &[Action::Collapse, Action::Expand]),
+	("Group", &[Action::Group]),
+	("Star", &[Action::Favorite]),
+	("Favorites", &[Action::FavoritesView]),
+];
+
+const DEFAULT: [(Action, &[&str]); 19] = [
+	(Action::Up, &["Up"]),
+	(Action::Down, &["Down"]),
+	(Action::PageUp, &["PageUp"]),
+	(Action::PageDown, &["PageDown"]),
+	(Action::First, &["Home"]),
+	(Action::Last, &["End"]),
+	(Action::Apply, &["Enter"]),
+	(Action::Quit, &["Esc"]),
+	(Action::Search, &["/"]),
+	(Action::Filter, &[":"]),
+	(Action::Favorite, &["f"]),
+	(Action::FavoritesView, &["F"]),
+	(Action::TogglePair, &["t"]),
+	(Action::Compare, &["c"]),
+	(Action::Random, &["r"]),
+	(Action::Expand, &["Right"]),
+	(Action::Collapse, &["Left"]),
+	(Action::Group, &["g"]),
+	(Action::Scene, &["s"]),
+];
+
+// The presets list their own keys first, so that those are shown in the keymap bar.
+const VIM: [(Action, &[&str]); 10] = [
+	(Action::Up, &["k", "Up"]),
+	(Action::Down, &["j", "Down"]),
+	(Action::PageUp, &["Ctrl-u", "PageUp"]),
+	(Action::PageDown, &["Ctrl-d", "PageDown"]),
+	(Action::First, &["g", "Home"]),
+	(Action::Last, &["G", "End"]),
+	(Action::Quit, &["q", "Esc"]),
+	(Action::Expand, &["l", "Right"]),
+	(Action::Collapse, &["h", "Left"]),
+	(Action::Group, &["z"]),
+];
+
+const EMACS: [(Action, &[&str]); 10] = [
+	(Action::Up, &["Ctrl-p", "Up"]),
+	(Action::Down, &["Ctrl-n", "Down"]),
+	(Action::PageUp, &["Alt-v", "PageUp"]),
+	(Action::PageDown, &["Ctrl-v", "PageDown"]),
+	(Action::First, &["Alt-<", "Home"]),
+	(Action::Last, &["Alt->", "End"]),
+	(Action::Quit, &["Ctrl-g", "Esc"]),
+	(Action::Search, &["Ctrl-s", "/"]),
+	(Action::Expand, &["Ctrl-f", "Right"]),
+	(Action::Collapse, &["Ctrl-b", "Left"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+	code: KeyCode,
+	/// Only Ctrl and Alt; Shift is part of the character.
+	modifiers: KeyModifiers,
+}
+
+impl Key {
+	fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+		Self {
+			code,
+			modifiers: modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+		}
+	}
+
+	/// Parses keys such as `j`, `G`, `Ctrl-d`, `C-d`, `Alt-<`, `M-<`, `PgDn`, `Shift-Tab` or `Esc`.
+	fn parse(text: &str) -> Result<Self> {
+		let mut modifiers = KeyModifiers::NONE;
+		let mut rest = text;
+		while let Some((prefix, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+			modifiers |= match prefix.to_lowercase().as_str() {
+				"ctrl" | "c" => KeyModifiers::CONTROL,
+				"alt" | "meta" | "m" => KeyModifiers::ALT,
+				_ => break,
+			};
+			rest = key;
+		}
+
+		let mut chars = rest.chars();
+		let code = match (chars.next(), chars.next()) {
+			(Some(c), None) => KeyCode::Char(c),
+			_ => match rest.to_lowercase().as_str() {
+				"up" => KeyCode::Up,
+				"down" => KeyCode::Down,
+				"left" => KeyCode::Left,
+				"right" => KeyCode::Right,
+				"pageup" | "pgup" => KeyCode::PageUp,
+				"pagedown" | "pgdn" => KeyCode::PageDown,
+				"home" => KeyCode::Home,
+				"end" => KeyCode::End,
+				"enter" | "return" => KeyCode::Enter,
+				"esc" | "escape" => KeyCode::Esc,
+				"tab" => KeyCode::Tab,
+				// Terminals send Shift-Tab as a key of its own.
+				"shift-tab" | "backtab" => KeyCode::BackTab,
+				"backspace" => KeyCode::Backspace,
+				"space" => KeyCode::Char(' '),
+				_ => return Err(format!("Unknown key '{text}'").into()),
+			},
+		};
+		Ok(Self::new(code, modifiers))
+	}
+
+	/// Whether the key types a character rather than issuing a command.
+	pub fn is_text(&self) -> bool {
+		matches!(self.code, KeyCode::Char(_)) && self.modifiers.is_empty()
+	}
+}
+
+impl From<KeyEvent> for Key {
+	fn from(event: KeyEvent) -> Self {
+		Self::new(event.code, event.modifiers)
+	}
+}
+
+impl fmt::Display for Key {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.modifiers.contains(KeyModifiers::CONTROL) {
+			write!(f, "Ctrl-")?;
+		}
+		if self.modifiers.contains(KeyModifiers::ALT) {
+			write!(f, "Alt-")?;
+		}
+		match self.code {
+			KeyCode::Char(' ') => write!(f, "Space"),
+			KeyCode::Char(c) => write!(f, "{c}"),
+			KeyCode::Up => write!(f, "↑"),
+			KeyCode::Down => write!(f, "↓"),
+			KeyCode::Left => write!(f, "←"),
+			KeyCode::Right => write!(f, "→"),
+			KeyCode::PageUp => write!(f, "PgUp"),
+			KeyCode::PageDown => write!(f, "PgDn"),
+			KeyCode::Enter => write!(f, "<Enter>"),
+			KeyCode::Esc => write!(f, "<Esc>"),
+			KeyCode::BackTab => write!(f, "Shift-Tab"),
+			code => write!(f, "{code:?}"),
+		}
+	}
+}
+
+pub struct Keymap {
+	bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Keymap {
+	/// The preset named in `settings`, with the actions `settings` rebinds.
+	pub fn new(settings: &KeymapSettings) -> Result<Self> {
+		let preset = settings.preset.as_deref().unwrap_or("default");
+		let overrides = match preset {
+			"default" => &[][..],
+			"vim" => &VIM[..],
+			"emacs" => &EMACS[..],
+			_ => return Err(format!("Unknown keymap preset '{preset}', expected default, vim or emacs").into()),
+		};
+
+		let mut keymap = Self { bindings: Vec::new() };
+		for (action, keys) in DEFAULT.iter().chain(overrides) {
+			keymap.bind(*action, keys.iter().map(|key| Key::parse(key)).collect::<Result<_>>()?);
+		}
+		for (name, keys) in &settings.bindings {
+			let action = Action::ALL
+				.into_iter()
+				.find(|action| action.name() == name)
+				.ok_or_else(|| format!("Unknown action '{name}'"))?;
+			let keys = match keys {
+				KeyList::One(key) => vec![Key::parse(key)?],
+				KeyList::Many(keys) => keys.iter().map(|key| Key::parse(key)).collect::<Result<_>>()?,
+			};
+			keymap.bind(action, keys);
+		}
+
+		for (action, keys) in &keymap.bindings {
+			for key in keys {
+				if let Some(other) = keymap.action(*key).filter(|other| other != action) {
+					return Err(format!(
+						"Key '{key}' is bound to both '{}' and '{}'",
+						other.name(),
+						action.name()
+					)
+					.into());
+				}
+			}
+		}
+		Ok(keymap)
+	}
+
+	fn bind(&mut self, action: Action, keys: Vec<Key>) {
+		match self.bindings.iter_mut().find(|(bound, _)| *bound == action) {
+			Some((_, bound)) => *bound = keys,
+			None => self.bindings.push((action, keys)),
+		}
+	}
+
+	pub fn action(&self, key: impl Into<Key>) -> Option<Action> {
+		let key = key.into();
+		self.bindings
+			.iter()
+			.find(|(_, keys)| keys.contains(&key))
+			.map(|(action, _)| *action)
+	}
+
+	/// The entries of the keymap bar, such as `("Navigate", "↑↓")`. Actions without keys are left
+	/// out.
+	pub fn hints(&self) -> Vec<(&'static str, String)> {
+		HINTS
+			.iter()
+			.filter_map(|(label, actions)| {
+				let keys: Vec<String> = actions
+					.iter()
+					.filter_map(|action| self.bindings.iter().find(|(bound, _)| bound == action))
+					.filter_map(|(_, keys)| keys.first())
+					.map(Key::to_string)
+					.collect();
+				if keys.is_empty() {
+					return None;
+				}
+				let separator = if keys.iter().all(|key| key.chars().count() == 1) {
+					""
+				} else {
+					"/"
+				};
+				Some((*label, keys.join(separator)))
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn keymap(settings: &str) -> Result<Keymap> {
+		Keymap::new(&toml::from_str(settings)?)
+	}
+
+	fn error(settings: &str) -> String {
+		keymap(settings).err().expect("the keymap is invalid").to_string()
+	}
+
+	fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+		Key::new(code, modifiers)
+	}
+
+	#[test]
+	fn parses_keys() {
+		let ctrl_x = key(KeyCode::Char('x'), KeyModifiers::CONTROL);
+		assert_eq!(Key::parse("ctrl-x").unwrap(), ctrl_x);
+		assert_eq!(Key::parse("Ctrl-x").unwrap(), ctrl_x);
+		assert_eq!(Key::parse("C-x").unwrap(), ctrl_x);
+		assert_eq!(
+			Key::parse("M-C-<").unwrap(),
+			key(KeyCode::Char('<'), KeyModifiers::CONTROL | KeyModifiers::ALT)
+		);
+		assert_eq!(Key::parse("G").unwrap(), key(KeyCode::Char('G'), KeyModifiers::NONE));
+		assert_eq!(Key::parse("-").unwrap(), key(KeyCode::Char('-'), KeyModifiers::NONE));
+		assert_eq!(Key::parse("PgDn").unwrap(), key(KeyCode::PageDown, KeyModifiers::NONE));
+		assert_eq!(
+			Key::parse("shift-Tab").unwrap(),
+			key(KeyCode::BackTab, KeyModifiers::NONE)
+		);
+		assert_eq!(Key::parse("shift-Tab").unwrap().to_string(), "Shift-Tab");
+		assert_eq!(Key::parse("Alt-Space").unwrap().to_string(), "Alt-Space");
+	}
+
+	#[test]
+	fn rejects_unknown_keys() {
+		for text in ["Ctrl-", "Hyper-x", "F13", "ctrl-xy", ""] {
+			assert_eq!(
+				Key::parse(text).unwrap_err().to_string(),
+				format!("Unknown key '{text}'")
+			);
+		}
+	}
+
+	#[test]
+	fn user_bindings_replace_those_of_the_preset() {
+		let keymap = keymap("preset = \"vim\"\nup = \"Ctrl-k\"\nsearch = [\"k\", \"?\"]").unwrap();
+		assert_eq!(keymap.action(Key::parse("Ctrl-k").unwrap()), Some(Action::Up));
+		assert_eq!(keymap.action(Key::parse("Up").unwrap()), None);
+		assert_eq!(keymap.action(Key::parse("k").unwrap()), Some(Action::Search));
+		assert_eq!(keymap.action(Key::parse("?").unwrap()), Some(Action::Search));
+		assert_eq!(keymap.action(Key::parse("/").unwrap()), None);
+		// The preset's other bindings stay.
+		assert_eq!(keymap.action(Key::parse("j").unwrap()), Some(Action::Down));
+		assert_eq!(keymap.action(Key::parse("f").unwrap()), Some(Action::Favorite));
+	}
+
+	#[test]
+	fn reports_keys_bound_twice() {
+		assert_eq!(
+			error("preset = \"vim\"\nsearch = \"k\""),
+			"Key 'k' is bound to both 'up' and 'search'"
+		);
+		assert_eq!(
+			error("quit = [\"q\", \"Ctrl-s\"]\nsearch = \"C-s\""),
+			"Key 'Ctrl-s' is bound to both 'quit' and 'search'"
+		);
+	}
+
+	#[test]
+	fn reports_unknown_presets_and_actions() {
+		assert_eq!(
+			error("preset = \"nano\""),
+			"Unknown keymap preset 'nano', expected default, vim or emacs"
+		);
+		assert_eq!(error("jump = \"J\""), "Unknown action 'jump'");
+	}
+}
@@ -1,18 +1,30 @@
+use cli::{parse_args, Command, USAGE};
+use commands::info::ThemeInfo;
+use commands::random::{self, choose, DEFAULT_NO_REPEAT};
 use crossterm::event::{
 	self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, MouseEvent, MouseEventKind,
 };
 use crossterm::execute;
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
 use dirs::config_dir;
+use families::{FavoritesView, Row, ThemeTree};
+use filter::Filter;
 use include_dir::{include_dir, Dir};
+use keymap::{Action, Keymap};
+use schedule::SystemClock;
+use search::Search;
+use settings::Settings;
+use state::State;
 use std::fs;
 use std::fs::create_dir_all;
 use std::io::{self, Write};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use structured_data::structs::{merge_colors, Colors};
+use store::watch::ThemeWatcher;
+use store::{Origin, ThemeEntry, ThemeStore};
+use structured_data::structs::{inherited_fields, merge_colors, Colors};
 use toml::Value;
 use tui::backend::CrosstermBackend;
 use tui::layout::{Constraint, Direction, Layout};
@@ -20,67 +32,244 @@ use tui::style::{Color, Modifier, Style};
 use tui::text::{Span, Spans};
 use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
 use tui::Terminal;
-use utils::example::return_example;
-
+use utils::example::{primary_style, return_example};
+use utils::scenes::Scene;
+use utils::swatches::swatches;
+
+mod appearance;
+mod cache;
+mod cli;
+mod commands;
+mod daemon;
+mod families;
+mod filter;
+mod keymap;
+mod pairs;
+mod schedule;
+mod search;
+mod settings;
+mod state;
+mod store;
 mod structured_data;
+mod sync;
 mod utils;
 
-const THEMES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/themes");
+static THEMES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/themes");
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-fn main() -> Result<()> {
+fn main() {
+	if let Err(err) = run() {
+		eprintln!("Error: {err}");
+		std::process::exit(1);
+	}
+}
+
+fn run() -> Result<()> {
+	let command = parse_args(std::env::args().skip(1))?;
+
+	let themes_path = config_dir()
+		.ok_or("Could not determine XDG_CONFIG directory")?
+		.join("alacritty_themes");
+
+	match command {
+		Command::Help => {
+			println!("{USAGE}");
+			return Ok(());
+		},
+		Command::Ctl(request) => {
+			println!("{}", daemon::send(&request)?);
+			return Ok(());
+		},
+		Command::Sync { dry_run } => return commands::sync::run(&themes_path, dry_run),
+		_ => {},
+	}
+
 	let config_path = config_dir()
 		.map(|path| path.join("alacritty/alacritty.toml"))
 		.ok_or("Could not determine Alacritty's config directory")?;
 
-	let original_config = fs::read_to_string(&config_path).unwrap_or_default();
+	let mut store = ThemeStore::open(&themes_path)?;
+	for warning in store.warnings() {
+		eprintln!("Warning: {warning}");
+	}
+	let settings = Settings::load()?;
+	match command {
+		Command::List(options) => commands::list::run(&options, &store),
+		Command::Random(options) => {
+			let default_theme = base_theme(&store, &settings)?;
+			random::run(&options, &store, &config_path, &default_theme)
+		},
+		Command::Schedule { once } => {
+			let default_theme = base_theme(&store, &settings)?;
+			commands::schedule::run(once, &SystemClock, &settings, |name| {
+				apply_theme_by_name(&store, &config_path, &default_theme, name)
+			})
+		},
+		Command::Toggle => commands::toggle::run(&store, &config_path, &base_theme(&store, &settings)?),
+		Command::Check => commands::check::run(&store, settings.base()),
+		Command::Info { name, json } => commands::info::run(&name, json, &store),
+		Command::Fav(action) => commands::fav::run(&action, &store),
+		Command::Tag(action) => commands::tag::run(&action, &store),
+		Command::Filter(action) => commands::filter::run(&action),
+		Command::FollowSystem => {
+			let default_theme = base_theme(&store, &settings)?;
+			commands::follow::run(&settings, |name| {
+				apply_theme_by_name(&store, &config_path, &default_theme, name)
+			})
+		},
+		Command::Daemon(options) => {
+			let default_theme = base_theme(&store, &settings)?;
+			daemon::serve(options, settings, themes_path, config_path, default_theme)
+		},
+		Command::Browse(options) => {
+			let default_theme = base_theme(&store, &settings)?;
+			let keymap = Keymap::new(&settings.keymap)
+				.map_err(|err| format!("Invalid keymap in alacritty_themes.toml: {err}"))?;
+			let sample = match &options.sample {
+				Some(path) => {
+					let content = read_preview(path, "sample")?;
+					Some(Scene::capture(file_name(path), &content))
+				},
+				None => None,
+			};
+			let source = match &options.preview_file {
+				Some(path) => {
+					let content = read_preview(path, "preview file")?;
+					let extension = path.extension().unwrap_or_default().to_string_lossy();
+					Some(Scene::source(file_name(path), &content, &extension))
+				},
+				None => None,
+			};
+			let scenes = Scene::all(sample, source);
+			browse(
+				&mut store,
+				&config_path,
+				&default_theme,
+				settings.base(),
+				&keymap,
+				&scenes,
+			)
+		},
+		// Handled before the themes are loaded.
+		Command::Help | Command::Ctl(_) | Command::Sync { .. } => Ok(()),
+	}
+}
+
+/// The colors a theme falls back to where it sets none, from the configured base theme. Only
+/// commands that apply or preview a theme need it, so a bad `base` doesn't break the others.
+fn base_theme(store: &ThemeStore, settings: &Settings) -> Result<Colors> {
+	let Some(base) = settings.base() else {
+		return Ok(Colors::default());
+	};
+	let entry = store.find(base).ok_or_else(|| {
+		format!("Base theme '{base}' not found. Set 'base' in alacritty_themes.toml to another theme, or to \"none\".")
+	})?;
+	Ok(store.load(entry).map_err(|err| format!("Base theme '{base}': {err}"))?)
+}
+
+/// Reads a file to preview themes with, which need not be valid UTF-8.
+fn read_preview(path: &Path, what: &str) -> Result<String> {
+	let content = fs::read(path).map_err(|err| format!("Could not read the {what} {}: {err}", path.display()))?;
+	Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+fn file_name(path: &Path) -> String {
+	path.file_name()
+		.unwrap_or(path.as_os_str())
+		.to_string_lossy()
+		.into_owned()
+}
+
+fn browse(
+	store: &mut ThemeStore,
+	config_path: &Path,
+	default_theme: &Colors,
+	base: Option<&str>,
+	keymap: &Keymap,
+	scenes: &[Scene],
+) -> Result<()> {
+	let original_config = fs::read_to_string(config_path).unwrap_or_default();
 	let original_colors = extract_colors_from_config(&original_config)?;
 
 	let running = Arc::new(AtomicBool::new(true));
 
-	ensure_themes_directory()?;
-	let themes_path = config_dir()
-		.ok_or("Could not determine XDG_CONFIG directory")?
-		.join("alacritty_themes");
-
-	let default_theme = load_theme(&themes_path.join("Default.dark.toml"))?;
+	let picker = Picker {
+		config_path,
+		default_theme,
+		original_colors: &original_colors,
+		base,
+		keymap,
+		scenes,
+	};
+	let result = select_theme_with_preview(store, &picker, &running);
 
-	let result = select_theme_with_preview(&themes_path, &config_path, &default_theme, &running);
+	if !running.load(Ordering::SeqCst) {
+		return restore_config(config_path, &original_colors);
+	}
 
-	if !running.load(Ordering::SeqCst) || result?.is_none() {
-		restore_config(&config_path, &original_colors)?;
+	match result? {
+		Some(name) => {
+			let mut state = State::load()?;
+			state.current = Some(name);
+			state.save()
+		},
+		None => restore_config(config_path, &original_colors),
 	}
+}
 
-	Ok(())
+/// The settings the theme picker works with.
+#[derive(Clone, Copy)]
+struct Picker<'a> {
+	config_path: &'a Path,
+	default_theme: &'a Colors,
+	/// The colors before the picker started, to compare themes with.
+	original_colors: &'a Colors,
+	base: Option<&'a str>,
+	keymap: &'a Keymap,
+	/// What the preview can show, the first one at the start.
+	scenes: &'a [Scene],
 }
 
 fn select_theme_with_preview(
-	themes_path: &Path,
-	config_path: &Path,
-	default_theme: &Colors,
+	store: &mut ThemeStore,
+	picker: &Picker,
 	running: &Arc<AtomicBool>,
-) -> Result<Option<PathBuf>> {
-	let entries = read_theme_entries(themes_path)?;
-	let theme_names = extract_theme_names(&entries);
-
+) -> Result<Option<String>> {
 	setup_terminal()?;
-	let result = run_event_loop(&entries, &theme_names, config_path, default_theme, running);
+	let result = run_event_loop(store, picker, running);
 	cleanup_terminal()?;
 
 	result
 }
 
-fn read_theme_entries(themes_path: &Path) -> Result<Vec<std::fs::DirEntry>> {
-	Ok(std::fs::read_dir(themes_path)?
-		.filter_map(std::result::Result::ok)
-		.collect())
-}
-
-fn extract_theme_names(entries: &[std::fs::DirEntry]) -> Vec<String> {
-	entries
+fn extract_theme_names(store: &ThemeStore) -> Vec<String> {
+	store
+		.entries()
 		.iter()
-		.filter_map(|entry| entry.file_name().into_string().ok())
+		.map(|entry| {
+			let mut name = entry.display_name();
+			// Broken themes are marked, the error is shown in the info panel.
+			if store.load(entry).is_err() {
+				name.insert_str(0, "⚠ ");
+			}
+			if entry.origin != Origin::Bundled {
+				name.push_str(&format!(" [{}]", entry.origin.label()));
+			}
+			if !entry.shadows.is_empty() {
+				name.push_str(" (shadows ");
+				name.push_str(
+					&entry
+						.shadows
+						.iter()
+						.map(|origin| origin.label())
+						.collect::<Vec<_>>()
+						.join(", "),
+				);
+				name.push(')');
+			}
+			name
+		})
 		.collect()
 }
 
@@ -99,73 +288,206 @@ fn cleanup_terminal() -> Result<()> {
 	Ok(())
 }
 
-fn run_event_loop(
-	entries: &[std::fs::DirEntry],
-	theme_names: &[String],
-	config_path: &Path,
-	default_theme: &Colors,
-	running: &Arc<AtomicBool>,
-) -> Result<Option<PathBuf>> {
+fn run_event_loop(store: &mut ThemeStore, picker: &Picker, running: &Arc<AtomicBool>) -> Result<Option<String>> {
+	let Picker {
+		config_path,
+		default_theme,
+		base,
+		keymap,
+		scenes,
+		..
+	} = *picker;
 	let backend = CrosstermBackend::new(io::stdout());
 	let mut terminal = Terminal::new(backend)?;
 
+	let mut state = State::load()?;
+	let mut theme_names = extract_theme_names(store);
+	let mut tree = ThemeTree::new(store.entries(), &state);
+	// Without a watcher, the list just doesn't follow changes to the themes directories.
+	let mut watcher = ThemeWatcher::new(store.layers()).ok();
 	let mut selected_index = 0;
-	let mut selected_path: Option<PathBuf> = None;
+	let mut selected_name: Option<String> = None;
 	let mut view_offset = 0;
 	let mut current_preview_index = usize::MAX;
+	let mut preview_colors = picker.original_colors.clone();
+	let mut prompt = FilterPrompt::default();
+	let mut search = Search::default();
+	// Whether the original colors are shown instead of the selected theme.
+	let mut comparing = false;
+	let mut scene = 0;
+	let mut info = Vec::new();
 
 	while running.load(Ordering::SeqCst) {
-		if selected_index != current_preview_index {
-			update_theme_preview(&entries[selected_index], config_path, default_theme)?;
-			current_preview_index = selected_index;
+		if watcher.as_mut().is_some_and(ThemeWatcher::changed) {
+			let selected = store.entries()[tree.entry_at(selected_index)].name.clone();
+			let searched = search.selected_name(store.entries()).map(String::from);
+			if store.reload().is_ok() {
+				let count = store.entries().len();
+				let mut visible = prompt.visible(store, default_theme, &state);
+				if !visible.contains(&true) {
+					prompt.applied.clear();
+					visible = vec![true; count];
+				}
+				theme_names = extract_theme_names(store);
+				selected_index = tree
+					.replace_entries(store.entries(), &state, visible, &selected)
+					.unwrap_or(selected_index.min(tree.rows().len() - 1));
+				search.update(store.entries(), &tree, &state, searched.as_deref());
+				// The selected theme may have been edited, so it is previewed again either way.
+				current_preview_index = usize::MAX;
+			}
+		}
+
+		let entries = store.entries();
+		let entry_index = search.selected_entry().unwrap_or_else(|| tree.entry_at(selected_index));
+		if entry_index != current_preview_index {
+			if let Some(colors) = update_theme_preview(store, &entries[entry_index], config_path, default_theme)? {
+				preview_colors = colors;
+			}
+			info = ThemeInfo::new(store, &entries[entry_index], &state).fields();
+			if let (Some(base), Ok(theme)) = (base, store.load(&entries[entry_index])) {
+				info.push(("Base", describe_inherited(base, default_theme, &theme)));
+			}
+			current_preview_index = entry_index;
+			comparing = false;
+		}
+
+		let mut title = match tree.favorites_view() {
+			FavoritesView::Mixed => "Themes",
+			FavoritesView::First => "Themes (favorites first)",
+			FavoritesView::Only => "Favorites",
+		}
+		.to_string();
+		if !prompt.applied.is_empty() {
+			title.push_str(&format!(" [{}]", prompt.applied));
+		}
+		if comparing {
+			title.push_str(" [original colors]");
 		}
 
-		draw_ui(&mut terminal, theme_names, selected_index, view_offset)?;
+		let (labels, selected, offset) = match search.query() {
+			Some(_) => {
+				title = format!("Search ({} of {})", search.matches().len(), entries.len());
+				let labels = search
+					.labels(entries, &theme_names)
+					.into_iter()
+					.map(|(label, positions)| highlight(label, &positions))
+					.collect();
+				(labels, search.selected, search.view_offset)
+			},
+			None => {
+				let labels = tree.labels(&theme_names).into_iter().map(Spans::from).collect();
+				(labels, selected_index, view_offset)
+			},
+		};
+		let input = match (&prompt.input, search.query()) {
+			(Some(input), _) => Some(InputBar {
+				title: "Filter (e.g. dark && contrast>7 && tag:retro)",
+				text: input,
+				error: prompt.error.as_deref(),
+			}),
+			(None, Some(query)) => Some(InputBar {
+				title: "Search names, tags and authors (Enter: jump, Esc: back)",
+				text: query,
+				error: search.matches().is_empty().then_some("No theme matches"),
+			}),
+			(None, None) => None,
+		};
+		let view = View {
+			title: &title,
+			labels,
+			selected_index: selected,
+			view_offset: offset,
+			input,
+			info: &info,
+			colors: if comparing {
+				picker.original_colors
+			} else {
+				&preview_colors
+			},
+			scene: &scenes[scene],
+		};
+		draw_ui(&mut terminal, view, keymap)?;
+		let visible_items = list_height(&terminal, keymap)?;
 
 		if !event::poll(Duration::from_millis(100))? {
 			continue;
 		}
 
 		match event::read()? {
-			Event::Key(key_event) => {
-				if !handle_key_event(
-					key_event,
-					&mut selected_index,
-					theme_names.len(),
-					&terminal,
-					&mut selected_path,
-					entries,
-				)? {
+			Event::Key(key_event) if prompt.input.is_some() => {
+				prompt.handle_key(key_event, &mut tree, store, default_theme, &state, &mut selected_index);
+			},
+			Event::Key(key_event) if search.query().is_some() => {
+				search.handle_key(key_event, keymap, entries, &mut tree, &state, &mut selected_index);
+			},
+			Event::Key(key_event) => match keymap.action(key_event) {
+				Some(Action::Filter) => prompt.open(),
+				Some(Action::Search) => search.open(entries, &tree, &state, selected_index),
+				Some(Action::Scene) => scene = (scene + 1) % scenes.len(),
+				Some(Action::Compare) => {
+					comparing = !comparing;
+					if comparing {
+						restore_config(config_path, picker.original_colors)?;
+					} else {
+						current_preview_index = usize::MAX;
+					}
+				},
+				Some(action)
+					if !handle_action(
+						action,
+						&mut selected_index,
+						&mut tree,
+						visible_items,
+						&mut selected_name,
+						entries,
+						&mut state,
+					)? =>
+				{
 					break;
-				}
+				},
+				_ => {},
+			},
+			Event::Mouse(mouse_event) if search.query().is_some() => {
+				let count = search.matches().len();
+				handle_mouse_event(
+					mouse_event,
+					&mut search.selected,
+					search.view_offset,
+					count,
+					visible_items,
+					&terminal,
+				)?;
 			},
 			Event::Mouse(mouse_event) => {
 				handle_mouse_event(
 					mouse_event,
 					&mut selected_index,
 					view_offset,
-					theme_names.len(),
+					tree.rows().len(),
+					visible_items,
 					&terminal,
 				)?;
 			},
 			_ => {},
 		}
 
-		adjust_view_offset(&terminal, &mut view_offset, selected_index)?;
+		adjust_view_offset(visible_items, &mut view_offset, selected_index);
+		adjust_view_offset(visible_items, &mut search.view_offset, search.selected);
 	}
 
-	Ok(selected_path)
+	Ok(selected_name)
 }
 
+#[allow(clippy::collapsible_match)]
 fn handle_mouse_event(
 	mouse_event: MouseEvent,
 	selected_index: &mut usize,
 	view_offset: usize,
 	theme_count: usize,
+	visible_items: usize,
 	terminal: &Terminal<CrosstermBackend<io::Stdout>>,
 ) -> Result<()> {
-	let terminal_height = terminal.size()?.height as usize;
-	let visible_items = terminal_height.saturating_sub(5);
 	let list_area_start = 1;
 	let list_area_end = visible_items + 1;
 
@@ -200,26 +522,103 @@ fn handle_mouse_event(
 	Ok(())
 }
 
-fn update_theme_preview(entry: &std::fs::DirEntry, config_path: &Path, default_theme: &Colors) -> Result<()> {
-	let path = entry.path();
-	if let Ok(theme) = load_theme(&path) {
-		let merged = merge_colors(default_theme, &theme);
-		update_alacritty_config(config_path, &merged)?;
+/// Which colors of `theme` come from the base theme, with groups taken over entirely shortened to
+/// `group.*`.
+fn describe_inherited(base: &str, default_theme: &Colors, theme: &Colors) -> String {
+	let inherited = inherited_fields(default_theme, theme);
+	if inherited.is_empty() {
+		return format!("nothing taken from {base}");
 	}
-	Ok(())
+
+	let all = inherited_fields(default_theme, &Colors::default());
+	let group_of = |field: &String| field.split('.').next().unwrap_or_default().to_string();
+	let mut parts: Vec<String> = Vec::new();
+	for field in &inherited {
+		let group = group_of(field);
+		let whole_group = all.iter().filter(|f| group_of(f) == group).count()
+			== inherited.iter().filter(|f| group_of(f) == group).count();
+		let part = if whole_group {
+			format!("{group}.*")
+		} else {
+			field.clone()
+		};
+		if !parts.contains(&part) {
+			parts.push(part);
+		}
+	}
+	format!("{} from {base}: {}", inherited.len(), parts.join(", "))
+}
+
+/// Returns the colors previewed. A broken theme leaves the preview as it was, its error is shown in
+/// the info panel instead.
+fn update_theme_preview(
+	store: &ThemeStore,
+	entry: &ThemeEntry,
+	config_path: &Path,
+	default_theme: &Colors,
+) -> Result<Option<Colors>> {
+	let Ok(theme) = store.load(entry) else {
+		return Ok(None);
+	};
+	let merged = merge_colors(default_theme, &theme);
+	update_alacritty_config(config_path, &merged)?;
+	Ok(Some(merged))
 }
 
-fn draw_ui(
-	terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-	theme_names: &[String],
+/// What the picker shows, besides the keymap.
+struct View<'a> {
+	title: &'a str,
+	labels: Vec<Spans<'a>>,
 	selected_index: usize,
 	view_offset: usize,
-) -> Result<()> {
+	input: Option<InputBar<'a>>,
+	info: &'a [(&'a str, String)],
+	/// The colors the preview is drawn in.
+	colors: &'a Colors,
+	scene: &'a Scene,
+}
+
+/// A line of text input shown in place of the keymap.
+struct InputBar<'a> {
+	title: &'a str,
+	text: &'a str,
+	error: Option<&'a str>,
+}
+
+/// `label` with the characters at `positions` highlighted.
+fn highlight(label: String, positions: &[usize]) -> Spans<'static> {
+	let style = Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED);
+	let mut spans = Vec::new();
+	let mut plain = String::new();
+	for (i, c) in label.chars().enumerate() {
+		if positions.contains(&i) {
+			spans.push(Span::raw(std::mem::take(&mut plain)));
+			spans.push(Span::styled(c.to_string(), style));
+		} else {
+			plain.push(c);
+		}
+	}
+	spans.push(Span::raw(plain));
+	Spans::from(spans)
+}
+
+fn draw_ui(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, view: View, keymap: &Keymap) -> Result<()> {
+	let View {
+		title,
+		labels,
+		selected_index,
+		view_offset,
+		input,
+		info,
+		colors,
+		scene,
+	} = view;
 	terminal.draw(|f| {
 		let terminal_size = f.size();
+		let keymap_lines = keymap_lines(keymap, terminal_size.width);
 		let main_chunks = Layout::default()
 			.direction(Direction::Vertical)
-			.constraints([Constraint::Min(5), Constraint::Length(3)])
+			.constraints([Constraint::Min(5), Constraint::Length(keymap_lines.len() as u16 + 2)])
 			.split(terminal_size);
 
 		let content_chunks = Layout::default()
@@ -229,12 +628,13 @@ fn draw_ui(
 
 		let visible_height = (content_chunks[0].height as usize).saturating_sub(2);
 
-		let items: Vec<ListItem> = theme_names
-			.iter()
+		let label_count = labels.len();
+		let items: Vec<ListItem> = labels
+			.into_iter()
 			.skip(view_offset)
 			.take(visible_height)
 			.enumerate()
-			.map(|(i, name)| {
+			.map(|(i, label)| {
 				let is_selected = i + view_offset == selected_index;
 				let mut style = if is_selected {
 					Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
@@ -243,106 +643,271 @@ fn draw_ui(
 				};
 
 				if (i == 0 && view_offset > 0)
-					|| (i == visible_height - 1 && view_offset + visible_height < theme_names.len())
+					|| (i == visible_height - 1 && view_offset + visible_height < label_count)
 				{
 					style = style.add_modifier(Modifier::DIM);
 				}
 
-				ListItem::new(Span::styled(name.clone(), style))
+				ListItem::new(label).style(style)
 			})
 			.collect();
 
 		let theme_list = List::new(items)
-			.block(Block::default().borders(Borders::ALL).title("Themes"))
+			.block(Block::default().borders(Borders::ALL).title(title))
 			.highlight_style(Style::default().bg(Color::Blue));
 
-		let example = return_example();
-		let preview = example.block(Block::default().borders(Borders::ALL).title("Preview"));
+		let palette_lines = swatches(colors);
+		let preview_chunks = Layout::default()
+			.direction(Direction::Vertical)
+			.constraints([
+				Constraint::Min(5),
+				Constraint::Length(palette_lines.len() as u16 + 2),
+				Constraint::Length(info.len() as u16 + 2),
+			])
+			.split(content_chunks[1]);
+
+		let example = return_example(colors, scene);
+		let preview = example.block(
+			Block::default()
+				.borders(Borders::ALL)
+				.title(format!("Preview: {}", scene.name())),
+		);
+
+		let palette = Paragraph::new(palette_lines)
+			.style(primary_style(colors))
+			.block(Block::default().borders(Borders::ALL).title("Palette"));
+
+		let info_lines: Vec<Spans> = info
+			.iter()
+			.map(|(label, value)| {
+				Spans::from(vec![
+					Span::styled(format!("{label:8} "), Style::default().add_modifier(Modifier::BOLD)),
+					Span::raw(value.clone()),
+				])
+			})
+			.collect();
+		let info_panel = Paragraph::new(info_lines).block(Block::default().borders(Borders::ALL).title("Info"));
 
 		f.render_widget(theme_list, content_chunks[0]);
-		f.render_widget(preview, content_chunks[1]);
-
-		let keybinds = Paragraph::new(Spans::from(vec![
-			Span::raw("Exit: "),
-			Span::styled("<Esc>", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-			Span::raw(" Select: "),
-			Span::styled(
-				"<Enter>",
-				Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
-			),
-			Span::raw(" Preview: "),
-			Span::styled("Click", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-			Span::raw(" Navigate: "),
-			Span::styled("↑↓", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-			Span::raw(" Page: "),
-			Span::styled(
-				"Scroll/PgUp/PgDn",
-				Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
-			),
-		]))
-		.block(Block::default().borders(Borders::ALL).title("Keymap"));
-
-		f.render_widget(keybinds, main_chunks[1]);
+		f.render_widget(preview, preview_chunks[0]);
+		f.render_widget(palette, preview_chunks[1]);
+		f.render_widget(info_panel, preview_chunks[2]);
+
+		let keybinds = Paragraph::new(keymap_lines).block(Block::default().borders(Borders::ALL).title("Keymap"));
+
+		match input {
+			Some(input) => {
+				let mut spans = vec![
+					Span::raw(input.text.to_string()),
+					Span::styled(" ", Style::default().bg(Color::White)),
+				];
+				if let Some(error) = input.error {
+					spans.push(Span::styled(format!("  {error}"), Style::default().fg(Color::Red)));
+				}
+				let bar =
+					Paragraph::new(Spans::from(spans)).block(Block::default().borders(Borders::ALL).title(input.title));
+				f.render_widget(bar, main_chunks[1]);
+			},
+			None => f.render_widget(keybinds, main_chunks[1]),
+		}
 	})?;
 	Ok(())
 }
 
-fn handle_key_event(
-	key_event: KeyEvent,
+/// The hints of the keymap bar, wrapped to fit a terminal `width` wide without splitting any.
+fn keymap_lines(keymap: &Keymap, width: u16) -> Vec<Spans<'static>> {
+	let width = usize::from(width.saturating_sub(2));
+	let key_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+	let hints = keymap
+		.hints()
+		.into_iter()
+		.chain([("Preview", "Click/Scroll".to_string())]);
+
+	let mut lines: Vec<Vec<Span>> = vec![Vec::new()];
+	let mut line_width = 0;
+	for (label, keys) in hints {
+		let hint_width = label.chars().count() + 2 + keys.chars().count();
+		if line_width > 0 && line_width + 1 + hint_width > width {
+			lines.push(Vec::new());
+			line_width = 0;
+		}
+		let Some(line) = lines.last_mut() else { break };
+		if line_width > 0 {
+			line.push(Span::raw(" "));
+			line_width += 1;
+		}
+		line.push(Span::raw(format!("{label}: ")));
+		line.push(Span::styled(keys, key_style));
+		line_width += hint_width;
+	}
+	lines.into_iter().map(Spans::from).collect()
+}
+
+/// How many themes the list shows, between its borders and above the keymap bar.
+fn list_height(terminal: &Terminal<CrosstermBackend<io::Stdout>>, keymap: &Keymap) -> Result<usize> {
+	let size = terminal.size()?;
+	let bar_height = keymap_lines(keymap, size.width).len() + 2;
+	Ok(usize::from(size.height).saturating_sub(bar_height + 2))
+}
+
+#[allow(clippy::collapsible_match)]
+fn handle_action(
+	action: Action,
 	selected_index: &mut usize,
-	theme_count: usize,
-	terminal: &Terminal<CrosstermBackend<io::Stdout>>,
-	selected_path: &mut Option<PathBuf>,
-	entries: &[std::fs::DirEntry],
+	tree: &mut ThemeTree,
+	visible_items: usize,
+	selected_name: &mut Option<String>,
+	entries: &[ThemeEntry],
+	state: &mut State,
 ) -> Result<bool> {
-	let terminal_height = terminal.size()?.height as usize;
-	let visible_items = terminal_height.saturating_sub(5);
 	let overlap = 3.min(visible_items / 4);
 	let page_jump = visible_items.saturating_sub(overlap);
+	let theme_count = tree.rows().len();
 
-	match key_event.code {
-		KeyCode::Down => {
+	match action {
+		Action::Down => {
 			if *selected_index < theme_count.saturating_sub(1) {
 				*selected_index += 1;
 			}
 		},
-		KeyCode::Up => {
+		Action::Up => {
 			if *selected_index > 0 {
 				*selected_index = selected_index.saturating_sub(1);
 			}
 		},
-		KeyCode::PageDown => {
+		Action::First => *selected_index = 0,
+		Action::Last => *selected_index = theme_count.saturating_sub(1),
+		Action::PageDown => {
 			if *selected_index + page_jump >= theme_count {
 				*selected_index = theme_count.saturating_sub(1);
 			} else {
 				*selected_index += page_jump;
 			}
 		},
-		KeyCode::PageUp => {
+		Action::PageUp => {
 			if *selected_index < page_jump {
 				*selected_index = 0;
 			} else {
 				*selected_index -= page_jump;
 			}
 		},
-		KeyCode::Enter => {
-			*selected_path = Some(entries[*selected_index].path());
+		Action::Apply if !tree.toggle(*selected_index) => {
+			*selected_name = Some(entries[tree.entry_at(*selected_index)].name.clone());
 			return Ok(false);
 		},
-		KeyCode::Esc => return Ok(false),
+		Action::Expand => tree.expand(*selected_index),
+		Action::Collapse => *selected_index = tree.collapse(*selected_index),
+		Action::Group => *selected_index = tree.toggle_grouped(*selected_index),
+		Action::Favorite if matches!(tree.rows()[*selected_index], Row::Theme { .. }) => {
+			let entry = tree.entry_at(*selected_index);
+			let favorite = state.toggle_favorite(&entries[entry].name);
+			state.save()?;
+			*selected_index = tree.set_favorite(entry, favorite, *selected_index);
+		},
+		Action::FavoritesView => *selected_index = tree.cycle_favorites_view(*selected_index),
+		Action::Random => {
+			let names: Vec<String> = entries
+				.iter()
+				.enumerate()
+				.filter(|(index, _)| tree.is_visible(*index))
+				.map(|(_, entry)| entry.name.clone())
+				.collect();
+			if let Some(index) =
+				choose(&names, state, DEFAULT_NO_REPEAT).and_then(|picked| names.iter().position(|name| name == picked))
+			{
+				state.record_pick(&names[index]);
+				let index = entries
+					.iter()
+					.position(|entry| entry.name == names[index])
+					.unwrap_or_default();
+				*selected_index = tree.reveal(index);
+				state.save()?;
+			}
+		},
+		Action::TogglePair => {
+			if let Some(index) = pairs::counterpart(entries, &entries[tree.entry_at(*selected_index)].name)
+				.and_then(|pair| entries.iter().position(|entry| entry.name == pair))
+				.filter(|&index| tree.is_visible(index))
+			{
+				*selected_index = tree.reveal(index);
+			}
+		},
+		Action::Quit => return Ok(false),
 		_ => {},
 	}
 
 	Ok(true)
 }
 
-fn adjust_view_offset(
-	terminal: &Terminal<CrosstermBackend<io::Stdout>>,
-	view_offset: &mut usize,
-	selected_index: usize,
-) -> Result<()> {
-	let terminal_height = terminal.size()?.height as usize;
-	let visible_items = terminal_height.saturating_sub(5);
+/// The `:` prompt that narrows the theme list down to a filter expression.
+#[derive(Default)]
+struct FilterPrompt {
+	/// The expression being edited while the prompt is open.
+	input: Option<String>,
+	/// The expression in effect, empty for none.
+	applied: String,
+	error: Option<String>,
+}
+
+impl FilterPrompt {
+	fn open(&mut self) {
+		self.input = Some(self.applied.clone());
+		self.error = None;
+	}
+
+	/// Handles `key_event` while the prompt is open.
+	fn handle_key(
+		&mut self,
+		key_event: KeyEvent,
+		tree: &mut ThemeTree,
+		store: &ThemeStore,
+		default_theme: &Colors,
+		state: &State,
+		selected_index: &mut usize,
+	) {
+		let Some(input) = &mut self.input else { return };
+
+		match key_event.code {
+			KeyCode::Char(c) => input.push(c),
+			KeyCode::Backspace => {
+				input.pop();
+			},
+			KeyCode::Esc => self.input = None,
+			KeyCode::Enter => match Filter::parse(input, state) {
+				Ok(filter) => {
+					let visible = matching(&filter, store, default_theme, state);
+					match tree.set_visible(visible, *selected_index) {
+						Some(row) => {
+							*selected_index = row;
+							self.applied = input.trim().to_string();
+							self.input = None;
+						},
+						None => self.error = Some("No theme matches".to_string()),
+					}
+				},
+				Err(err) => self.error = Some(err.to_string()),
+			},
+			_ => {},
+		}
+	}
+
+	/// Which themes the applied filter lets through. A filter that no longer parses, say because
+	/// a saved filter it uses was removed, lets everything through.
+	fn visible(&self, store: &ThemeStore, default_theme: &Colors, state: &State) -> Vec<bool> {
+		let filter = Filter::parse(&self.applied, state).unwrap_or(Filter::All(Vec::new()));
+		matching(&filter, store, default_theme, state)
+	}
+}
+
+fn matching(filter: &Filter, store: &ThemeStore, default_theme: &Colors, state: &State) -> Vec<bool> {
+	store
+		.entries()
+		.iter()
+		.map(|entry| filter.matches_entry(store, entry, default_theme, state))
+		.collect()
+}
+
+fn adjust_view_offset(visible_items: usize, view_offset: &mut usize, selected_index: usize) {
 	let buffer_zone = 3.min(visible_items / 4);
 
 	if selected_index < *view_offset + buffer_zone {
@@ -350,8 +915,6 @@ fn adjust_view_offset(
 	} else if selected_index >= *view_offset + visible_items.saturating_sub(buffer_zone) {
 		*view_offset = selected_index.saturating_sub(visible_items.saturating_sub(buffer_zone + 1));
 	}
-
-	Ok(())
 }
 
 fn extract_colors_from_config(config_content: &str) -> Result<Colors> {
@@ -389,33 +952,22 @@ fn update_alacritty_config(config_path: &Path, colors: &Colors) -> Result<()> {
 
 	table.insert("colors".to_string(), Value::Table(colors_table.clone()));
 
+	if let Some(parent) = config_path.parent() {
+		create_dir_all(parent)?;
+	}
 	fs::write(config_path, toml::to_string_pretty(&config)?)?;
 	Ok(())
 }
 
-fn load_theme(path: &Path) -> Result<Colors> {
-	let content = fs::read_to_string(path)?;
-	let config: Value = toml::from_str(&content)?;
-	let colors = config
-		.get("colors")
-		.ok_or("No colors section found")?
-		.clone()
-		.try_into()?;
-	Ok(colors)
-}
-
-fn ensure_themes_directory() -> Result<()> {
-	let Some(config_home) = config_dir() else { return Ok(()) };
-
-	let themes_path = config_home.join("alacritty_themes");
-	if themes_path.exists() {
-		return Ok(());
-	}
-
-	create_dir_all(&themes_path)?;
-	for file in THEMES_DIR.files() {
-		fs::write(themes_path.join(file.path()), file.contents())?;
-	}
-
-	Ok(())
+/// Applies the named theme and records it as the current one.
+fn apply_theme_by_name(store: &ThemeStore, config_path: &Path, default_theme: &Colors, name: &str) -> Result<()> {
+	let entry = store.get(name)?;
+	let theme = store
+		.load(entry)
+		.map_err(|err| format!("Theme '{}': {err}", entry.name))?;
+	update_alacritty_config(config_path, &merge_colors(default_theme, &theme))?;
+
+	let mut state = State::load()?;
+	state.current = Some(entry.name.clone());
+	state.save()
 }
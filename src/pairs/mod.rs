@@ -0,0 +1,198 @@
+//! Light/dark counterparts of themes, either declared through `[metadata] pair = "..."` in a
+//! theme file or detected from names such as `Embers.dark`/`Embers.light`,
+//! `Gruvbox-Dark`/`Gruvbox-Light` or `github_dark_default`/`github_light_default`. A variant
+//! suffix also pairs with the bare name, as in `Alabaster.dark`/`Alabaster`.
+
+use crate::store::ThemeEntry;
+
+const VARIANT_PAIRS: [(&str, &str); 2] = [("dark", "light"), ("night", "day")];
+const SEPARATORS: [char; 4] = ['.', '-', '_', ' '];
+
+/// Finds the counterpart of `name` among `names` purely from the naming convention.
+pub fn detect_counterpart<'a>(name: &str, names: &'a [String]) -> Option<&'a String> {
+	let mut start = 0;
+	for (end, separator) in name.char_indices().chain([(name.len(), ' ')]) {
+		if !SEPARATORS.contains(&separator) {
+			continue;
+		}
+
+		let word = &name[start..end];
+		if let Some(opposite) = opposite_variant(word) {
+			let candidate = format!("{}{}{}", &name[..start], match_case(opposite, word), &name[end..]);
+			let found = names
+				.iter()
+				.find(|other| **other == candidate)
+				.or_else(|| names.iter().find(|other| other.eq_ignore_ascii_case(&candidate)));
+			if found.is_some() {
+				return found;
+			}
+		}
+		start = end + separator.len_utf8();
+	}
+
+	suffix_counterpart(name, names)
+}
+
+/// Pairs `Name.dark` with a bare `Name`, and a bare name with its only suffixed variant.
+fn suffix_counterpart<'a>(name: &str, names: &'a [String]) -> Option<&'a String> {
+	let find = |candidate: &str| {
+		names
+			.iter()
+			.find(|other| **other == candidate)
+			.or_else(|| names.iter().find(|other| other.eq_ignore_ascii_case(candidate)))
+	};
+
+	if let Some((base, word)) = name.rsplit_once('.') {
+		if opposite_variant(word).is_some() {
+			return find(base);
+		}
+	}
+
+	let mut variants = VARIANT_PAIRS
+		.iter()
+		.flat_map(|(a, b)| [a, b])
+		.filter_map(|variant| find(&format!("{name}.{variant}")));
+	match (variants.next(), variants.next()) {
+		(Some(variant), None) => Some(variant),
+		_ => None,
+	}
+}
+
+fn opposite_variant(word: &str) -> Option<&'static str> {
+	VARIANT_PAIRS.iter().find_map(|(a, b)| {
+		if word.eq_ignore_ascii_case(a) {
+			Some(*b)
+		} else if word.eq_ignore_ascii_case(b) {
+			Some(*a)
+		} else {
+			None
+		}
+	})
+}
+
+fn match_case(word: &str, template: &str) -> String {
+	if template.chars().all(|c| c.is_ascii_uppercase()) {
+		word.to_ascii_uppercase()
+	} else if template.starts_with(|c: char| c.is_ascii_uppercase()) {
+		let mut chars = word.chars();
+		chars
+			.next()
+			.map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+			.unwrap_or_default()
+	} else {
+		word.to_string()
+	}
+}
+
+/// Finds the counterpart of `name` among `entries`. An explicit `pair` in the theme's metadata
+/// wins, then a theme that declares `name` as its pair, then the naming convention.
+pub fn counterpart(entries: &[ThemeEntry], name: &str) -> Option<String> {
+	let names: Vec<String> = entries.iter().map(|entry| entry.name.clone()).collect();
+
+	let declared = entries
+		.iter()
+		.find(|entry| entry.name == name)
+		.and_then(|entry| entry.metadata().ok())
+		.and_then(|metadata| metadata.pair)
+		.filter(|pair| names.contains(pair));
+	if declared.is_some() {
+		return declared;
+	}
+
+	let declared_by = entries.iter().find(|entry| {
+		entry
+			.metadata()
+			.is_ok_and(|metadata| metadata.pair.as_deref() == Some(name))
+	});
+	if let Some(entry) = declared_by {
+		return Some(entry.name.clone());
+	}
+
+	detect_counterpart(name, &names).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn names(names: &[&str]) -> Vec<String> {
+		names.iter().map(ToString::to_string).collect()
+	}
+
+	fn bundled_names() -> Vec<String> {
+		crate::THEMES_DIR
+			.files()
+			.filter_map(|file| file.path().file_stem()?.to_str().map(String::from))
+			.collect()
+	}
+
+	#[test]
+	fn swaps_variant_words() {
+		let names = names(&[
+			"Gruvbox-Dark",
+			"Gruvbox-Light",
+			"github_dark_default",
+			"github_light_default",
+			"Tokyo Night",
+			"Tokyo Day",
+		]);
+		assert_eq!(detect_counterpart("Gruvbox-Dark", &names).unwrap(), "Gruvbox-Light");
+		assert_eq!(
+			detect_counterpart("github_light_default", &names).unwrap(),
+			"github_dark_default"
+		);
+		assert_eq!(detect_counterpart("Tokyo Night", &names).unwrap(), "Tokyo Day");
+	}
+
+	#[test]
+	fn pairs_suffix_with_bare_name() {
+		let names = names(&[
+			"Alabaster",
+			"Alabaster.dark",
+			"Ocean",
+			"Ocean.dark",
+			"Ocean.light",
+			"vscode.dark",
+		]);
+		assert_eq!(detect_counterpart("Alabaster.dark", &names).unwrap(), "Alabaster");
+		assert_eq!(detect_counterpart("Alabaster", &names).unwrap(), "Alabaster.dark");
+		assert_eq!(detect_counterpart("Ocean.dark", &names).unwrap(), "Ocean.light");
+		// Which of its two variants a bare name stands for is unknown.
+		assert_eq!(detect_counterpart("Ocean", &names), None);
+		assert_eq!(detect_counterpart("vscode.dark", &names), None);
+	}
+
+	#[test]
+	fn bundled_pairs_are_symmetric() {
+		let names = bundled_names();
+		assert!(!names.is_empty());
+		for name in &names {
+			let Some(counterpart) = detect_counterpart(name, &names) else {
+				continue;
+			};
+			assert_ne!(counterpart, name);
+			assert_eq!(
+				detect_counterpart(counterpart, &names),
+				Some(name),
+				"counterpart of {counterpart}"
+			);
+		}
+	}
+
+	#[test]
+	fn bundled_variants_have_counterparts() {
+		let names = bundled_names();
+		for name in &names {
+			let Some((base, _)) = name.rsplit_once('.') else {
+				continue;
+			};
+			let has_sibling = names.iter().any(|other| {
+				other != name && (other == base || other.strip_prefix(base).is_some_and(|rest| rest.starts_with('.')))
+			});
+			if has_sibling {
+				assert!(detect_counterpart(name, &names).is_some(), "{name} has no counterpart");
+			}
+		}
+		assert_eq!(detect_counterpart("Alabaster.dark", &names).unwrap(), "Alabaster");
+	}
+}
@@ -0,0 +1,289 @@
+use chrono::{DateTime, Days, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone};
+use solar::{sun_events, SunEvents};
+
+use crate::settings::{ScheduleSettings, SolarSettings};
+use crate::Result;
+
+pub mod solar;
+
+/// Source of the current time, injectable so schedules can be evaluated at arbitrary moments.
+pub trait Clock {
+	fn now(&self) -> DateTime<FixedOffset>;
+	fn sleep_until(&self, deadline: DateTime<FixedOffset>);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> DateTime<FixedOffset> {
+		Local::now().fixed_offset()
+	}
+
+	fn sleep_until(&self, deadline: DateTime<FixedOffset>) {
+		if let Ok(duration) = (deadline - self.now()).to_std() {
+			std::thread::sleep(duration);
+		}
+	}
+}
+
+/// The theme a schedule selects at some moment, and when that selection ends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slot {
+	pub theme: String,
+	pub until: DateTime<FixedOffset>,
+}
+
+/// Evaluates the schedule at `now`. Returns `None` when nothing is scheduled.
+pub fn active_slot(schedule: &ScheduleSettings, now: DateTime<FixedOffset>) -> Result<Option<Slot>> {
+	if let Some(solar) = &schedule.solar {
+		return Ok(Some(solar_slot(solar, now)));
+	}
+
+	let mut times = schedule
+		.times
+		.iter()
+		.map(|(time, theme)| {
+			NaiveTime::parse_from_str(time, "%H:%M")
+				.map(|time| (time, theme))
+				.map_err(|_| format!("Invalid schedule time '{time}', expected HH:MM").into())
+		})
+		.collect::<Result<Vec<_>>>()?;
+	times.sort();
+
+	let (Some(first), Some(last)) = (times.first(), times.last()) else {
+		return Ok(None);
+	};
+
+	let time = now.time();
+	let current = times.iter().rev().find(|(start, _)| *start <= time).unwrap_or(last);
+	let until = match times.iter().find(|(start, _)| *start > time) {
+		Some((start, _)) => local_datetime(now, now.date_naive(), *start),
+		None => local_datetime(now, next_day(now.date_naive()), first.0),
+	};
+
+	Ok(Some(Slot {
+		theme: current.1.clone(),
+		until,
+	}))
+}
+
+fn solar_slot(solar: &SolarSettings, now: DateTime<FixedOffset>) -> Slot {
+	let today = now.date_naive();
+	let tomorrow = next_day(today);
+	let day = |until| Slot {
+		theme: solar.day.clone(),
+		until,
+	};
+	let night = |until| Slot {
+		theme: solar.night.clone(),
+		until,
+	};
+
+	match sun_events(today, solar.latitude, solar.longitude) {
+		SunEvents::Regular { sunrise, sunset } => {
+			let sunrise = sunrise.with_timezone(now.offset());
+			let sunset = sunset.with_timezone(now.offset());
+			if now < sunrise {
+				night(sunrise)
+			} else if now < sunset {
+				day(sunset)
+			} else {
+				let until = match sun_events(tomorrow, solar.latitude, solar.longitude) {
+					SunEvents::Regular { sunrise, .. } => sunrise.with_timezone(now.offset()),
+					SunEvents::PolarDay | SunEvents::PolarNight => local_datetime(now, tomorrow, NaiveTime::MIN),
+				};
+				night(until)
+			}
+		},
+		SunEvents::PolarDay => day(local_datetime(now, tomorrow, NaiveTime::MIN)),
+		SunEvents::PolarNight => night(local_datetime(now, tomorrow, NaiveTime::MIN)),
+	}
+}
+
+fn next_day(date: NaiveDate) -> NaiveDate {
+	date.checked_add_days(Days::new(1)).unwrap_or(date)
+}
+
+fn local_datetime(now: DateTime<FixedOffset>, date: NaiveDate, time: NaiveTime) -> DateTime<FixedOffset> {
+	now.offset()
+		.from_local_datetime(&date.and_time(time))
+		.single()
+		.unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::cell::{Cell, RefCell};
+
+	use crate::commands::schedule::run;
+	use crate::settings::Settings;
+
+	/// A clock that stands still until it is asked to sleep, then jumps to the deadline.
+	struct FakeClock(Cell<DateTime<FixedOffset>>);
+
+	impl Clock for FakeClock {
+		fn now(&self) -> DateTime<FixedOffset> {
+			self.0.get()
+		}
+
+		fn sleep_until(&self, deadline: DateTime<FixedOffset>) {
+			self.0.set(deadline);
+		}
+	}
+
+	fn at(value: &str) -> DateTime<FixedOffset> {
+		DateTime::parse_from_rfc3339(value).unwrap()
+	}
+
+	fn times(entries: &[(&str, &str)]) -> ScheduleSettings {
+		ScheduleSettings {
+			times: entries
+				.iter()
+				.map(|(time, theme)| (time.to_string(), theme.to_string()))
+				.collect(),
+			solar: None,
+		}
+	}
+
+	fn solar(latitude: f64, longitude: f64) -> ScheduleSettings {
+		ScheduleSettings {
+			times: Default::default(),
+			solar: Some(SolarSettings {
+				latitude,
+				longitude,
+				day: "Day".into(),
+				night: "Night".into(),
+			}),
+		}
+	}
+
+	#[test]
+	fn slot_wraps_past_midnight() {
+		let schedule = times(&[("07:00", "Light"), ("20:00", "Dark")]);
+
+		let slot = active_slot(&schedule, at("2024-03-10T23:30:00+01:00"))
+			.unwrap()
+			.unwrap();
+		assert_eq!(slot.theme, "Dark");
+		assert_eq!(slot.until, at("2024-03-11T07:00:00+01:00"));
+
+		let slot = active_slot(&schedule, at("2024-03-11T02:00:00+01:00"))
+			.unwrap()
+			.unwrap();
+		assert_eq!(slot.theme, "Dark");
+		assert_eq!(slot.until, at("2024-03-11T07:00:00+01:00"));
+
+		let slot = active_slot(&schedule, at("2024-03-11T07:00:00+01:00"))
+			.unwrap()
+			.unwrap();
+		assert_eq!(slot.theme, "Light");
+		assert_eq!(slot.until, at("2024-03-11T20:00:00+01:00"));
+	}
+
+	#[test]
+	fn empty_schedule_has_no_slot() {
+		assert_eq!(active_slot(&times(&[]), at("2024-03-10T12:00:00Z")).unwrap(), None);
+	}
+
+	#[test]
+	fn invalid_time_is_rejected() {
+		for time in ["25:00", "7am", "07:60", ""] {
+			let err = active_slot(&times(&[(time, "Dark")]), at("2024-03-10T12:00:00Z")).unwrap_err();
+			assert_eq!(
+				err.to_string(),
+				format!("Invalid schedule time '{time}', expected HH:MM")
+			);
+		}
+	}
+
+	#[test]
+	fn known_sunrise_and_sunset() {
+		// London at the June solstice: sunrise 04:43 and sunset 21:21 BST.
+		let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+		let SunEvents::Regular { sunrise, sunset } = sun_events(date, 51.5074, -0.1278) else {
+			panic!("expected a regular day");
+		};
+		let minutes_off =
+			|actual: DateTime<chrono::Utc>, expected: &str| (actual.fixed_offset() - at(expected)).num_minutes().abs();
+		assert!(
+			minutes_off(sunrise, "2024-06-21T04:43:00+01:00") <= 2,
+			"sunrise at {sunrise}"
+		);
+		assert!(
+			minutes_off(sunset, "2024-06-21T21:21:00+01:00") <= 2,
+			"sunset at {sunset}"
+		);
+	}
+
+	#[test]
+	fn solar_slot_switches_at_sunrise_and_sunset() {
+		let schedule = solar(51.5074, -0.1278);
+
+		let slot = active_slot(&schedule, at("2024-06-21T03:00:00+01:00"))
+			.unwrap()
+			.unwrap();
+		assert_eq!(slot.theme, "Night");
+		let sunrise = slot.until;
+
+		let slot = active_slot(&schedule, sunrise).unwrap().unwrap();
+		assert_eq!(slot.theme, "Day");
+		let sunset = slot.until;
+		assert!(sunset - sunrise > chrono::TimeDelta::hours(16));
+
+		let slot = active_slot(&schedule, sunset).unwrap().unwrap();
+		assert_eq!(slot.theme, "Night");
+		assert_eq!(slot.until.date_naive(), NaiveDate::from_ymd_opt(2024, 6, 22).unwrap());
+	}
+
+	#[test]
+	fn polar_day_and_night() {
+		// Tromsø, well north of the Arctic Circle.
+		let (latitude, longitude) = (69.6492, 18.9553);
+		let summer = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+		let winter = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+		assert!(matches!(sun_events(summer, latitude, longitude), SunEvents::PolarDay));
+		assert!(matches!(sun_events(winter, latitude, longitude), SunEvents::PolarNight));
+
+		let schedule = solar(latitude, longitude);
+		let slot = active_slot(&schedule, at("2024-06-21T12:00:00+02:00"))
+			.unwrap()
+			.unwrap();
+		assert_eq!(slot.theme, "Day");
+		assert_eq!(slot.until, at("2024-06-22T00:00:00+02:00"));
+
+		let slot = active_slot(&schedule, at("2024-12-21T12:00:00+01:00"))
+			.unwrap()
+			.unwrap();
+		assert_eq!(slot.theme, "Night");
+		assert_eq!(slot.until, at("2024-12-22T00:00:00+01:00"));
+	}
+
+	#[test]
+	fn run_applies_only_transitions() {
+		let settings = Settings {
+			schedule: times(&[("07:00", "Light"), ("20:00", "Dark")]),
+			..Default::default()
+		};
+		let clock = FakeClock(Cell::new(at("2024-03-10T19:58:30+00:00")));
+		let applied = RefCell::new(Vec::new());
+
+		let result = run(false, &clock, &settings, |theme| {
+			applied.borrow_mut().push((clock.now(), theme.to_string()));
+			if applied.borrow().len() == 3 {
+				return Err("stop".into());
+			}
+			Ok(())
+		});
+
+		assert_eq!(result.unwrap_err().to_string(), "stop");
+		assert_eq!(
+			applied.into_inner(),
+			[
+				(at("2024-03-10T19:58:30+00:00"), "Light".to_string()),
+				(at("2024-03-10T20:00:00+00:00"), "Dark".to_string()),
+				(at("2024-03-11T07:00:00+00:00"), "Light".to_string()),
+			]
+		);
+	}
+}
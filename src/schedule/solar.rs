@@ -0,0 +1,61 @@
+//! Offline sunrise/sunset computation based on the sunrise equation, accurate to a minute or two,
+//! which is plenty for switching themes.
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+const J2000: f64 = 2_451_545.0;
+const UNIX_EPOCH_JULIAN: f64 = 2_440_587.5;
+/// Sun altitude at sunrise/sunset, accounting for refraction and the solar disc radius.
+const HORIZON_DEGREES: f64 = -0.833;
+const EARTH_TILT_DEGREES: f64 = 23.4397;
+
+pub enum SunEvents {
+	Regular {
+		sunrise: DateTime<Utc>,
+		sunset: DateTime<Utc>,
+	},
+	/// The sun stays above the horizon for the whole day.
+	PolarDay,
+	/// The sun stays below the horizon for the whole day.
+	PolarNight,
+}
+
+/// Computes sunrise and sunset for `date` at the given position, with longitude positive east.
+pub fn sun_events(date: NaiveDate, latitude: f64, longitude: f64) -> SunEvents {
+	let j2000_date = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap_or_default();
+	#[allow(clippy::cast_precision_loss)]
+	let days = (date - j2000_date).num_days() as f64;
+
+	let mean_solar_noon = days + 0.0009 - longitude / 360.0;
+	let mean_anomaly = (357.5291 + 0.985_600_28 * mean_solar_noon).rem_euclid(360.0);
+	let m = mean_anomaly.to_radians();
+	let center = 1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+	let ecliptic_longitude = (mean_anomaly + center + 180.0 + 102.9372)
+		.rem_euclid(360.0)
+		.to_radians();
+	let transit = J2000 + mean_solar_noon + 0.0053 * m.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+
+	let declination = (ecliptic_longitude.sin() * EARTH_TILT_DEGREES.to_radians().sin()).asin();
+	let phi = latitude.to_radians();
+	let cos_hour_angle =
+		(HORIZON_DEGREES.to_radians().sin() - phi.sin() * declination.sin()) / (phi.cos() * declination.cos());
+
+	if cos_hour_angle < -1.0 {
+		return SunEvents::PolarDay;
+	}
+	if cos_hour_angle > 1.0 {
+		return SunEvents::PolarNight;
+	}
+
+	let hour_angle = cos_hour_angle.acos().to_degrees();
+	SunEvents::Regular {
+		sunrise: julian_to_utc(transit - hour_angle / 360.0),
+		sunset: julian_to_utc(transit + hour_angle / 360.0),
+	}
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn julian_to_utc(julian: f64) -> DateTime<Utc> {
+	let seconds = ((julian - UNIX_EPOCH_JULIAN) * 86_400.0).round() as i64;
+	DateTime::from_timestamp(seconds, 0).unwrap_or_default()
+}
@@ -0,0 +1,191 @@
+//! Incremental fuzzy search over the theme list, by display name, tag and author. While searching,
+//! the list shows just the matches, best first.
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::families::ThemeTree;
+use crate::filter::theme_tags;
+use crate::keymap::{Action, Key, Keymap};
+use crate::state::State;
+use crate::store::ThemeEntry;
+use crate::utils::fuzzy::fuzzy_match;
+
+/// Points a tag or author match loses against a match in the name.
+const DETAIL_PENALTY: i32 = 8;
+
+pub struct Match {
+	pub entry: usize,
+	score: i32,
+	/// The matched characters of the display name.
+	positions: Vec<usize>,
+	/// A tag or author that matched better than the name, with what it is and the matched
+	/// characters.
+	detail: Option<(&'static str, String, Vec<usize>)>,
+}
+
+#[derive(Default)]
+pub struct Search {
+	/// What is searched for, while searching.
+	query: Option<String>,
+	matches: Vec<Match>,
+	pub selected: usize,
+	pub view_offset: usize,
+}
+
+impl Search {
+	pub fn query(&self) -> Option<&str> {
+		self.query.as_deref()
+	}
+
+	pub fn matches(&self) -> &[Match] {
+		&self.matches
+	}
+
+	/// The theme selected among the matches, while searching.
+	pub fn selected_entry(&self) -> Option<usize> {
+		self.query.as_ref()?;
+		self.matches.get(self.selected).map(|found| found.entry)
+	}
+
+	/// Starts searching, with the theme at `selected_index` selected.
+	pub fn open(&mut self, entries: &[ThemeEntry], tree: &ThemeTree, state: &State, selected_index: usize) {
+		self.query = Some(String::new());
+		self.update(entries, tree, state, None);
+		let current = tree.entry_at(selected_index);
+		self.selected = self
+			.matches
+			.iter()
+			.position(|found| found.entry == current)
+			.unwrap_or(0);
+	}
+
+	/// Handles `key_event` while searching. Characters are typed into the query, other keys move
+	/// through the matches as bound in `keymap`. Enter ends the search at the selected match, Esc
+	/// goes back to where it started.
+	pub fn handle_key(
+		&mut self,
+		key_event: KeyEvent,
+		keymap: &Keymap,
+		entries: &[ThemeEntry],
+		tree: &mut ThemeTree,
+		state: &State,
+		selected_index: &mut usize,
+	) {
+		let last = self.matches.len().saturating_sub(1);
+		match (key_event.code, keymap.action(key_event)) {
+			(KeyCode::Char(c), _) if Key::from(key_event).is_text() => {
+				self.query.get_or_insert_default().push(c);
+				self.restart(entries, tree, state);
+			},
+			(KeyCode::Backspace, _) => {
+				self.query.get_or_insert_default().pop();
+				self.restart(entries, tree, state);
+			},
+			(KeyCode::Enter, _) | (_, Some(Action::Apply)) => {
+				if let Some(entry) = self.selected_entry() {
+					*selected_index = tree.reveal(entry);
+				}
+				self.close();
+			},
+			(KeyCode::Esc, _) | (_, Some(Action::Quit)) => self.close(),
+			(_, Some(Action::Down)) => self.selected = (self.selected + 1).min(last),
+			(_, Some(Action::Up)) => self.selected = self.selected.saturating_sub(1),
+			(_, Some(Action::First)) => self.selected = 0,
+			(_, Some(Action::Last)) => self.selected = last,
+			_ => {},
+		}
+	}
+
+	fn close(&mut self) {
+		*self = Self::default();
+	}
+
+	/// Searches again with a changed query, selecting the best match.
+	fn restart(&mut self, entries: &[ThemeEntry], tree: &ThemeTree, state: &State) {
+		self.update(entries, tree, state, None);
+		self.selected = 0;
+		self.view_offset = 0;
+	}
+
+	/// The name of the theme selected among the matches, while searching.
+	pub fn selected_name<'a>(&self, entries: &'a [ThemeEntry]) -> Option<&'a str> {
+		self.selected_entry().map(|entry| entries[entry].name.as_str())
+	}
+
+	/// Finds the matches among the themes shown in `tree`, selecting the theme called `selected`
+	/// if it matches, or else the best match. Does nothing unless searching.
+	pub fn update(&mut self, entries: &[ThemeEntry], tree: &ThemeTree, state: &State, selected: Option<&str>) {
+		let Some(query) = &self.query else { return };
+
+		self.matches = entries
+			.iter()
+			.enumerate()
+			.filter(|(index, _)| tree.is_visible(*index))
+			.filter_map(|(index, entry)| find(query, index, entry, state))
+			.collect();
+		// A stable sort keeps the list order among equally good matches.
+		self.matches.sort_by_key(|found| std::cmp::Reverse(found.score));
+
+		self.selected = self
+			.matches
+			.iter()
+			.position(|found| Some(entries[found.entry].name.as_str()) == selected)
+			.unwrap_or(0);
+	}
+
+	/// The list labels for all matches, given the label of each theme, with the positions of the
+	/// matched characters.
+	pub fn labels(&self, entries: &[ThemeEntry], theme_names: &[String]) -> Vec<(String, Vec<usize>)> {
+		self.matches
+			.iter()
+			.map(|found| {
+				let mut label = theme_names[found.entry].clone();
+				// Theme labels may add markers around the display name.
+				let offset = label
+					.find(&entries[found.entry].display_name())
+					.map_or(0, |start| label[..start].chars().count());
+				let mut positions: Vec<usize> = found.positions.iter().map(|position| position + offset).collect();
+
+				if let Some((kind, detail, detail_positions)) = &found.detail {
+					label.push_str(&format!("  ({kind} "));
+					let offset = label.chars().count();
+					positions.extend(detail_positions.iter().map(|position| position + offset));
+					label.push_str(detail);
+					label.push(')');
+				}
+				(label, positions)
+			})
+			.collect()
+	}
+}
+
+fn find(query: &str, entry: usize, theme: &ThemeEntry, state: &State) -> Option<Match> {
+	let name = fuzzy_match(query, &theme.display_name()).map(|(score, positions)| Match {
+		entry,
+		score,
+		positions,
+		detail: None,
+	});
+
+	let author = theme.metadata().ok().and_then(|metadata| metadata.author);
+	let details = theme_tags(theme, state)
+		.into_iter()
+		.map(|tag| ("tag", tag))
+		.chain(author.map(|author| ("by", author)));
+	let detail = details
+		.filter_map(|(kind, detail)| {
+			let (score, positions) = fuzzy_match(query, &detail)?;
+			Some(Match {
+				entry,
+				score: score - DETAIL_PENALTY,
+				positions: Vec::new(),
+				detail: Some((kind, detail, positions)),
+			})
+		})
+		.max_by_key(|found| found.score);
+
+	match (name, detail) {
+		(Some(name), Some(detail)) if detail.score > name.score => Some(detail),
+		(name, detail) => name.or(detail),
+	}
+}
@@ -0,0 +1,103 @@
+use dirs::config_dir;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::appearance::ColorScheme;
+use crate::Result;
+
+/// User configuration for the tool itself, read from `alacritty_themes.toml` in the XDG config
+/// directory. Every section is optional.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Settings {
+	/// The theme every other theme is applied on top of, so that colors a theme leaves out keep a
+	/// sensible value. `"none"` applies themes as they are.
+	pub base: Option<String>,
+	pub schedule: ScheduleSettings,
+	pub appearance: Option<AppearanceSettings>,
+	pub keymap: KeymapSettings,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ScheduleSettings {
+	/// Local `HH:MM` times mapped to the theme that becomes active at that time.
+	pub times: BTreeMap<String, String>,
+	/// When present, takes precedence over `times`.
+	pub solar: Option<SolarSettings>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SolarSettings {
+	pub latitude: f64,
+	pub longitude: f64,
+	pub day: String,
+	pub night: String,
+}
+
+/// Key bindings of the theme picker, see [`Keymap`](crate::keymap::Keymap).
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct KeymapSettings {
+	/// `default`, `vim` or `emacs`.
+	pub preset: Option<String>,
+	/// Keys by action name, replacing those of the preset.
+	#[serde(flatten)]
+	pub bindings: BTreeMap<String, KeyList>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum KeyList {
+	One(String),
+	Many(Vec<String>),
+}
+
+/// Themes to use when following the desktop's light/dark preference.
+#[derive(Deserialize, Debug, Clone)]
+pub struct AppearanceSettings {
+	pub light: String,
+	pub dark: String,
+}
+
+impl AppearanceSettings {
+	pub fn theme_for(&self, scheme: ColorScheme) -> &str {
+		if scheme.is_dark() {
+			&self.dark
+		} else {
+			&self.light
+		}
+	}
+}
+
+/// The base theme when none is configured.
+const DEFAULT_BASE: &str = "Default.dark";
+
+impl Settings {
+	/// The name of the base theme, or `None` if themes are applied without one.
+	pub fn base(&self) -> Option<&str> {
+		match self.base.as_deref() {
+			Some(base) if base.eq_ignore_ascii_case("none") => None,
+			Some(base) => Some(base),
+			None => Some(DEFAULT_BASE),
+		}
+	}
+
+	pub fn path() -> Option<PathBuf> {
+		config_dir().map(|path| path.join("alacritty_themes.toml"))
+	}
+
+	/// Loads the settings file. A missing file yields the defaults, a malformed one is an error.
+	pub fn load() -> Result<Self> {
+		let Some(path) = Self::path() else {
+			return Ok(Self::default());
+		};
+		let Ok(content) = fs::read_to_string(&path) else {
+			return Ok(Self::default());
+		};
+
+		toml::from_str(&content).map_err(|err| format!("Invalid settings in {}: {err}", path.display()).into())
+	}
+}
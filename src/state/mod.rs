@@ -0,0 +1,138 @@
+use dirs::{data_local_dir, state_dir};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::Result;
+
+/// How many past picks are remembered, regardless of how many are avoided.
+const HISTORY_LIMIT: usize = 100;
+const STATE_FILE: &str = "state.toml";
+
+/// Where the state file `name` lives, in the XDG state directory.
+pub fn state_file(name: &str) -> Option<PathBuf> {
+	state_dir()
+		.or_else(data_local_dir)
+		.map(|path| path.join("alacritty_themes").join(name))
+}
+
+/// Reads the state file `name`. A missing file yields the default, a malformed one is an error, so
+/// that saving does not overwrite what is in it.
+pub fn load_state_file<T: DeserializeOwned + Default>(name: &str) -> Result<T> {
+	let Some(path) = state_file(name) else {
+		return Ok(T::default());
+	};
+	let content = match fs::read_to_string(&path) {
+		Ok(content) => content,
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(T::default()),
+		Err(err) => return Err(format!("Could not read {}: {err}", path.display()).into()),
+	};
+
+	toml::from_str(&content)
+		.map_err(|err| format!("Invalid state in {}, fix or remove it: {err}", path.display()).into())
+}
+
+pub fn save_state_file(name: &str, value: &impl Serialize) -> Result<()> {
+	let path = state_file(name).ok_or("Could not determine XDG_STATE directory")?;
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+	fs::write(path, toml::to_string_pretty(value)?)?;
+	Ok(())
+}
+
+/// Persistent, per-user state that is not configuration: history, favorites, tags and saved
+/// filters.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct State {
+	/// The theme that was last applied for good, as opposed to merely previewed.
+	pub current: Option<String>,
+	/// Recently picked theme names, most recent last.
+	pub recent: Vec<String>,
+	pub favorites: Vec<String>,
+	/// The user's own tags by theme name, in addition to those from theme metadata.
+	pub tags: BTreeMap<String, Vec<String>>,
+	/// Filter expressions by name.
+	pub filters: BTreeMap<String, String>,
+}
+
+impl State {
+	/// Loads the state file, or an empty state if there is none yet.
+	pub fn load() -> Result<Self> {
+		load_state_file(STATE_FILE)
+	}
+
+	pub fn save(&self) -> Result<()> {
+		save_state_file(STATE_FILE, self)
+	}
+
+	pub fn record_pick(&mut self, name: &str) {
+		self.recent.retain(|recent| recent != name);
+		self.recent.push(name.to_string());
+		if self.recent.len() > HISTORY_LIMIT {
+			self.recent.drain(..self.recent.len() - HISTORY_LIMIT);
+		}
+	}
+
+	/// The last `count` picks, most recent last.
+	pub fn last_picks(&self, count: usize) -> &[String] {
+		&self.recent[self.recent.len().saturating_sub(count)..]
+	}
+
+	pub fn is_favorite(&self, name: &str) -> bool {
+		self.favorites.iter().any(|favorite| favorite == name)
+	}
+
+	/// Returns false if `name` already was a favorite.
+	pub fn add_favorite(&mut self, name: &str) -> bool {
+		if self.is_favorite(name) {
+			return false;
+		}
+		self.favorites.push(name.to_string());
+		true
+	}
+
+	/// Returns false if `name` was not a favorite.
+	pub fn remove_favorite(&mut self, name: &str) -> bool {
+		let count = self.favorites.len();
+		self.favorites.retain(|favorite| favorite != name);
+		self.favorites.len() != count
+	}
+
+	/// Stars or unstars `name` and returns whether it is a favorite now.
+	pub fn toggle_favorite(&mut self, name: &str) -> bool {
+		!self.remove_favorite(name) && self.add_favorite(name)
+	}
+
+	pub fn has_tag(&self, name: &str, tag: &str) -> bool {
+		self.tags
+			.get(name)
+			.is_some_and(|tags| tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+	}
+
+	/// Returns false if `name` already had the tag.
+	pub fn add_tag(&mut self, name: &str, tag: &str) -> bool {
+		if self.has_tag(name, tag) {
+			return false;
+		}
+		self.tags.entry(name.to_string()).or_default().push(tag.to_string());
+		true
+	}
+
+	/// Returns false if `name` did not have the tag.
+	pub fn remove_tag(&mut self, name: &str, tag: &str) -> bool {
+		let Some(tags) = self.tags.get_mut(name) else {
+			return false;
+		};
+		let count = tags.len();
+		tags.retain(|t| !t.eq_ignore_ascii_case(tag));
+		let removed = tags.len() != count;
+		if tags.is_empty() {
+			self.tags.remove(name);
+		}
+		removed
+	}
+}
@@ -0,0 +1,709 @@
+//! Theme lookup over a layered search path. From lowest to highest precedence the layers are:
+//! the themes bundled into the binary (read straight from it, so nothing has to be extracted),
+//! `alacritty-themes` in each of `$XDG_DATA_DIRS`, the user's themes directory, and
+//! `.alacritty-themes` in the current directory and its ancestors, nearest last. A theme in a
+//! higher layer shadows the theme of the same name below it.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{Cache, Stamp, Stamps};
+use crate::structured_data::structs::{color_fields, merge_colors, Colors, Metadata};
+use crate::utils::color::{parse_hex, Stats};
+use crate::utils::names::{display_name, natural_cmp};
+use crate::{Result, THEMES_DIR};
+
+pub mod watch;
+
+const SYSTEM_THEMES_DIR: &str = "alacritty-themes";
+const PROJECT_THEMES_DIR: &str = ".alacritty-themes";
+const SIDECAR_SUFFIX: &str = ".meta.toml";
+/// Colors that Alacritty accepts in place of a hex color, for the cursor and the like.
+const CELL_COLORS: [&str; 2] = ["CellForeground", "CellBackground"];
+
+/// The layer of the search path a theme comes from, in increasing order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+	Bundled,
+	System,
+	User,
+	Project,
+}
+
+impl Origin {
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Bundled => "bundled",
+			Self::System => "system",
+			Self::User => "user",
+			Self::Project => "project",
+		}
+	}
+}
+
+/// A directory on the search path.
+#[derive(Debug, Clone)]
+pub struct Layer {
+	pub origin: Origin,
+	pub path: PathBuf,
+}
+
+/// The directory layers above the bundled themes, lowest precedence first.
+pub fn search_path(user_path: &Path) -> Vec<Layer> {
+	let data_dirs = std::env::var("XDG_DATA_DIRS")
+		.ok()
+		.filter(|dirs| !dirs.is_empty())
+		.unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+	// Earlier entries of `$XDG_DATA_DIRS` are more important, so they are layered last.
+	let mut layers: Vec<Layer> = std::env::split_paths(&data_dirs)
+		.filter(|dir| dir.is_absolute())
+		.map(|dir| Layer {
+			origin: Origin::System,
+			path: dir.join(SYSTEM_THEMES_DIR),
+		})
+		.collect();
+	layers.reverse();
+
+	layers.push(Layer {
+		origin: Origin::User,
+		path: user_path.to_path_buf(),
+	});
+
+	if let Ok(current_dir) = std::env::current_dir() {
+		let mut projects: Vec<Layer> = current_dir
+			.ancestors()
+			.map(|dir| dir.join(PROJECT_THEMES_DIR))
+			.filter(|dir| dir.is_dir())
+			.map(|path| Layer {
+				origin: Origin::Project,
+				path,
+			})
+			.collect();
+		projects.reverse();
+		layers.extend(projects);
+	}
+
+	layers
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+	Embedded(&'static [u8]),
+	File(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct ThemeEntry {
+	/// The name a theme is identified by: its path relative to its themes directory, without the
+	/// `.toml` extension.
+	pub name: String,
+	pub origin: Origin,
+	/// Lower layers that have a different theme under the same name, highest first.
+	pub shadows: Vec<Origin>,
+	source: Source,
+	/// The theme file's contents, or why they could not be read.
+	parsed: std::result::Result<Parsed, String>,
+}
+
+/// Everything read from a theme file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Parsed {
+	pub theme: ThemeFile,
+	pub metadata: Metadata,
+	/// Why the theme's metadata could not be read. The colors are usable regardless.
+	#[serde(default)]
+	pub metadata_error: Option<String>,
+	/// Figures about the theme's colors. `None` for themes that extend another, as their colors
+	/// depend on it.
+	pub stats: Option<Stats>,
+}
+
+impl ThemeEntry {
+	pub fn display_name(&self) -> String {
+		match self
+			.parsed
+			.as_ref()
+			.ok()
+			.and_then(|parsed| parsed.metadata.name.clone())
+		{
+			Some(title) => title,
+			None => display_name(self.name.rsplit_once('/').map_or(self.name.as_str(), |(_, name)| name)),
+		}
+	}
+
+	/// The subdirectory of its themes directory the theme lives in, if any.
+	pub fn category(&self) -> Option<&str> {
+		self.name.rsplit_once('/').map(|(category, _)| category)
+	}
+
+	pub fn path(&self) -> Option<&Path> {
+		match &self.source {
+			Source::Embedded(_) => None,
+			Source::File(path) => Some(path),
+		}
+	}
+
+	fn bytes(&self) -> Result<Cow<'static, [u8]>> {
+		match &self.source {
+			Source::Embedded(content) => Ok(Cow::Borrowed(content)),
+			Source::File(path) => Ok(Cow::Owned(fs::read(path)?)),
+		}
+	}
+
+	pub fn content(&self) -> Result<Cow<'static, str>> {
+		match &self.source {
+			Source::Embedded(content) => Ok(String::from_utf8_lossy(content)),
+			Source::File(path) => Ok(Cow::Owned(fs::read_to_string(path)?)),
+		}
+	}
+
+	pub fn parsed(&self) -> Result<&Parsed> {
+		self.parsed.as_ref().map_err(|err| err.clone().into())
+	}
+
+	pub fn metadata(&self) -> Result<Metadata> {
+		Ok(self.parsed()?.metadata.clone())
+	}
+
+	pub fn metadata_error(&self) -> Option<&str> {
+		self.parsed.as_ref().ok()?.metadata_error.as_deref()
+	}
+
+	pub fn stats(&self) -> Option<&Stats> {
+		self.parsed.as_ref().ok()?.stats.as_ref()
+	}
+
+	fn parse(&self) -> Result<Parsed> {
+		let content = self.content()?;
+		let theme = parse_theme(&content)?;
+		let stats = match theme.extends {
+			Some(_) => None,
+			None => theme.colors.as_ref().and_then(Stats::of),
+		};
+		// Metadata is extra information, so a mistake in it leaves the theme usable.
+		let (metadata, metadata_error) = match self.read_metadata(&content) {
+			Ok(metadata) => (metadata, None),
+			Err(err) => (
+				parse_header_comments(&content),
+				Some(format!("Invalid metadata: {err}")),
+			),
+		};
+		Ok(Parsed {
+			theme,
+			metadata,
+			metadata_error,
+			stats,
+		})
+	}
+
+	/// The theme's metadata: its `[metadata]` table, then a `NAME.meta.toml` sidecar file next to
+	/// it, then `# Author: ...` style header comments.
+	fn read_metadata(&self, content: &str) -> Result<Metadata> {
+		let mut metadata = parse_metadata(content)?;
+		if let Some(sidecar) = self.sidecar_path().filter(|path| path.is_file()) {
+			let sidecar_metadata = toml::from_str(&fs::read_to_string(&sidecar)?)
+				.map_err(|err| format!("{}: {err}", sidecar.display()))?;
+			metadata = metadata.or(sidecar_metadata);
+		}
+		Ok(metadata.or(parse_header_comments(content)))
+	}
+
+	fn cache_key(&self) -> String {
+		match &self.source {
+			Source::Embedded(_) => format!("bundled:{}", self.name),
+			Source::File(path) => path.to_string_lossy().into_owned(),
+		}
+	}
+
+	/// `None` if the theme file can't be inspected, so that it is not cached.
+	fn stamps(&self) -> Option<Stamps> {
+		match &self.source {
+			Source::Embedded(content) => Some((Stamp::embedded(content), None)),
+			Source::File(path) => Some((Stamp::of(path)?, self.sidecar_path().and_then(|path| Stamp::of(&path)))),
+		}
+	}
+
+	fn sidecar_path(&self) -> Option<PathBuf> {
+		let path = self.path()?;
+		let stem = path.file_stem()?.to_string_lossy();
+		Some(path.with_file_name(format!("{stem}{SIDECAR_SUFFIX}")))
+	}
+}
+
+pub struct ThemeStore {
+	entries: Vec<ThemeEntry>,
+	layers: Vec<Layer>,
+	/// Directories of the layers that could not be read, and why.
+	warnings: Vec<String>,
+}
+
+impl ThemeStore {
+	/// Lists the bundled themes, overlaid with the search path built around `user_path`.
+	pub fn open(user_path: &Path) -> Result<Self> {
+		Self::open_layers(&search_path(user_path))
+	}
+
+	/// Lists the bundled themes, overlaid with `layers` in order. Missing directories are skipped,
+	/// and so are unreadable ones, with a warning.
+	pub fn open_layers(layers: &[Layer]) -> Result<Self> {
+		let mut themes: BTreeMap<String, ThemeEntry> = BTreeMap::new();
+		let mut warnings = Vec::new();
+
+		for file in THEMES_DIR.files() {
+			let Some(name) = theme_stem(file.path()) else { continue };
+			themes.insert(
+				name.clone(),
+				ThemeEntry {
+					name,
+					origin: Origin::Bundled,
+					shadows: Vec::new(),
+					source: Source::Embedded(file.contents()),
+					parsed: Err(String::new()),
+				},
+			);
+		}
+
+		for layer in layers {
+			let mut files = Vec::new();
+			collect_theme_files(&layer.path, "", &mut HashSet::new(), &mut files, &mut warnings);
+
+			for (name, path) in files {
+				let mut entry = ThemeEntry {
+					name: name.clone(),
+					origin: layer.origin,
+					shadows: Vec::new(),
+					source: Source::File(path),
+					parsed: Err(String::new()),
+				};
+
+				if let Some(shadowed) = themes.get(&name) {
+					// An identical copy, such as an extracted bundled theme, shadows nothing.
+					if entry.bytes().ok() == shadowed.bytes().ok() {
+						continue;
+					}
+					entry.shadows.push(shadowed.origin);
+					entry.shadows.extend(&shadowed.shadows);
+				}
+				themes.insert(name, entry);
+			}
+		}
+
+		let mut entries: Vec<ThemeEntry> = themes.into_values().collect();
+		parse_entries(&mut entries);
+
+		let mut titled: Vec<(String, ThemeEntry)> =
+			entries.into_iter().map(|entry| (entry.display_name(), entry)).collect();
+		titled.sort_by(|(a_title, a), (b_title, b)| natural_cmp(a_title, b_title).then_with(|| a.name.cmp(&b.name)));
+
+		Ok(Self {
+			entries: titled.into_iter().map(|(_, entry)| entry).collect(),
+			layers: layers.to_vec(),
+			warnings,
+		})
+	}
+
+	/// Reads the themes again, after files were added, removed or edited.
+	pub fn reload(&mut self) -> Result<()> {
+		*self = Self::open_layers(&self.layers)?;
+		Ok(())
+	}
+
+	pub fn warnings(&self) -> &[String] {
+		&self.warnings
+	}
+
+	pub fn layers(&self) -> &[Layer] {
+		&self.layers
+	}
+
+	pub fn entries(&self) -> &[ThemeEntry] {
+		&self.entries
+	}
+
+	/// Looks a theme up by name, with or without the `.toml` extension.
+	pub fn find(&self, name: &str) -> Option<&ThemeEntry> {
+		let name = name.strip_suffix(".toml").unwrap_or(name);
+		self.entries.iter().find(|entry| entry.name == name)
+	}
+
+	pub fn get(&self, name: &str) -> Result<&ThemeEntry> {
+		self.find(name).ok_or_else(|| format!("Unknown theme '{name}'").into())
+	}
+
+	/// Loads the colors of `entry`, on top of those of the theme it `extends`, if any.
+	pub fn load(&self, entry: &ThemeEntry) -> Result<Colors> {
+		self.load_extending(entry, &mut Vec::new())
+	}
+
+	/// `chain` holds the themes extended by `entry`, to detect cycles.
+	fn load_extending(&self, entry: &ThemeEntry, chain: &mut Vec<String>) -> Result<Colors> {
+		let theme = &entry.parsed()?.theme;
+		let Some(parent) = &theme.extends else {
+			return theme.colors.clone().ok_or_else(|| "No colors section found".into());
+		};
+
+		chain.push(entry.name.clone());
+		let parent = self
+			.find(parent)
+			.ok_or_else(|| format!("'{}' extends unknown theme '{parent}'", entry.name))?;
+		if chain.contains(&parent.name) {
+			chain.push(parent.name.clone());
+			return Err(format!("Themes extend each other in a cycle: {}", chain.join(" -> ")).into());
+		}
+
+		let base = self
+			.load_extending(parent, chain)
+			.map_err(|err| format!("'{}' extends '{}': {err}", entry.name, parent.name))?;
+		Ok(match &theme.colors {
+			Some(colors) => merge_colors(&base, colors),
+			None => base,
+		})
+	}
+}
+
+/// The theme name for `path`, or `None` for anything that is not a theme: files without a `.toml`
+/// extension (READMEs, editor backups), metadata sidecars and hidden files (editor swap and lock
+/// files).
+fn theme_stem(path: &Path) -> Option<String> {
+	if path.extension()? != "toml" || path.to_string_lossy().ends_with(SIDECAR_SUFFIX) {
+		return None;
+	}
+	let stem = path.file_stem()?.to_string_lossy();
+	if stem.starts_with('.') {
+		return None;
+	}
+	Some(stem.into_owned())
+}
+
+/// Parses all `entries`, or takes them from the cache. Whatever is not cached is parsed in
+/// parallel, and the cache is updated for next time.
+fn parse_entries(entries: &mut [ThemeEntry]) {
+	let previous = Cache::load();
+	let mut cache = Cache::new();
+	let mut misses = Vec::new();
+
+	for (index, entry) in entries.iter_mut().enumerate() {
+		let key = entry.cache_key();
+		let hit = entry
+			.stamps()
+			.and_then(|stamps| Some((stamps, previous.get(&key, stamps)?)));
+		match hit {
+			Some((stamps, parsed)) => {
+				entry.parsed = parsed.clone();
+				cache.insert(key, stamps, parsed.clone());
+			},
+			None => misses.push(index),
+		}
+	}
+
+	let threads = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+	let chunk_size = misses.len().div_ceil(threads).max(1);
+	// Along with each result, whether it is worth caching.
+	let parsed: Vec<(usize, std::result::Result<Parsed, String>, bool)> = std::thread::scope(|scope| {
+		let entries = &*entries;
+		let workers: Vec<_> = misses
+			.chunks(chunk_size)
+			.map(|chunk| {
+				scope.spawn(move || {
+					chunk
+						.iter()
+						.map(|&index| {
+							let (result, cacheable) = parse_catching(&entries[index]);
+							(index, result, cacheable)
+						})
+						.collect::<Vec<_>>()
+				})
+			})
+			.collect();
+		workers
+			.into_iter()
+			.flat_map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+			.collect()
+	});
+
+	for (index, result, cacheable) in parsed {
+		let entry = &mut entries[index];
+		if let Some(stamps) = entry.stamps().filter(|_| cacheable) {
+			cache.insert(entry.cache_key(), stamps, result.clone());
+		}
+		entry.parsed = result;
+	}
+
+	if !misses.is_empty() || cache.len() != previous.len() {
+		// The cache only saves time, so a read-only cache directory is no reason to fail.
+		let _ = cache.save();
+	}
+}
+
+thread_local! {
+	static PARSING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Parses `entry`, turning a panic into an error that only marks this theme broken, and is not
+/// worth caching. Such panics are not printed, as the picker may own the terminal.
+fn parse_catching(entry: &ThemeEntry) -> (std::result::Result<Parsed, String>, bool) {
+	static QUIET_HOOK: Once = Once::new();
+	QUIET_HOOK.call_once(|| {
+		let hook = panic::take_hook();
+		panic::set_hook(Box::new(move |info| {
+			if !PARSING.with(Cell::get) {
+				hook(info);
+			}
+		}));
+	});
+
+	PARSING.with(|parsing| parsing.set(true));
+	let result = panic::catch_unwind(AssertUnwindSafe(|| entry.parse()));
+	PARSING.with(|parsing| parsing.set(false));
+	match result {
+		Ok(parsed) => (parsed.map_err(|err| err.to_string()), true),
+		Err(panic) => (Err(format!("The parser crashed: {}", panic_message(&*panic))), false),
+	}
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+	panic
+		.downcast_ref::<&str>()
+		.copied()
+		.or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+		.unwrap_or("unknown error")
+}
+
+/// Collects the themes below `dir` as `(name, path)` pairs. Themes in subdirectories are named
+/// after their relative path, e.g. `retro/Amber` for `retro/Amber.toml`.
+///
+/// `visited` holds the canonical paths of the directories read so far, so that a symlink to a
+/// directory above it is read only once. Directories that can't be read are skipped, with a
+/// warning unless they are missing.
+fn collect_theme_files(
+	dir: &Path,
+	prefix: &str,
+	visited: &mut HashSet<PathBuf>,
+	files: &mut Vec<(String, PathBuf)>,
+	warnings: &mut Vec<String>,
+) {
+	let entries = fs::canonicalize(dir).and_then(|canonical| {
+		if visited.insert(canonical) {
+			fs::read_dir(dir).map(Some)
+		} else {
+			Ok(None)
+		}
+	});
+	let entries = match entries {
+		Ok(Some(entries)) => entries,
+		Ok(None) => return,
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+		Err(err) => {
+			warnings.push(format!("Skipped {}: {err}", dir.display()));
+			return;
+		},
+	};
+
+	for entry in entries.filter_map(std::result::Result::ok) {
+		let path = entry.path();
+		let file_name = entry.file_name().to_string_lossy().into_owned();
+
+		if path.is_dir() {
+			if !file_name.starts_with('.') {
+				collect_theme_files(&path, &format!("{prefix}{file_name}/"), visited, files, warnings);
+			}
+		} else if let Some(stem) = theme_stem(&path) {
+			files.push((format!("{prefix}{stem}"), path));
+		}
+	}
+}
+
+/// The parts of a theme file that make up its colors.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThemeFile {
+	/// The name of the theme this one builds on.
+	pub extends: Option<String>,
+	pub colors: Option<Colors>,
+}
+
+/// Parses a theme file and checks its colors. Errors say where in `content` the problem is.
+///
+/// Only `colors` and `extends` are read, so nothing else in a theme file, such as `[metadata]`,
+/// can end up in `alacritty.toml`.
+pub fn parse_theme(content: &str) -> Result<ThemeFile> {
+	let theme: ThemeFile = toml::from_str(content)?;
+	for (path, color) in theme.colors.as_ref().map(color_fields).unwrap_or_default() {
+		if parse_hex(&color).is_none() && !CELL_COLORS.contains(&color.as_str()) {
+			let location = locate(content, &color)
+				.map(|(line, column)| format!(" at line {line}, column {column}"))
+				.unwrap_or_default();
+			return Err(
+				format!("Invalid color{location}\n'{color}' for colors.{path}, expected #rrggbb or 0xrrggbb").into(),
+			);
+		}
+	}
+	Ok(theme)
+}
+
+pub fn parse_metadata(content: &str) -> Result<Metadata> {
+	#[derive(Deserialize)]
+	struct MetadataTable {
+		#[serde(default)]
+		metadata: Metadata,
+	}
+
+	Ok(toml::from_str::<MetadataTable>(content)?.metadata)
+}
+
+/// The line and column, both counted from 1, where the string `value` is first written in
+/// `content`.
+fn locate(content: &str, value: &str) -> Option<(usize, usize)> {
+	let offset = [format!("\"{value}\""), format!("'{value}'")]
+		.iter()
+		.filter_map(|quoted| content.find(quoted.as_str()))
+		.min()?;
+	let line_start = content[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+	let line = content[..offset].matches('\n').count() + 1;
+	Some((line, content[line_start..offset].chars().count() + 1))
+}
+
+/// Metadata from the comments at the top of a theme file, as in the bundled themes:
+///
+/// ```toml
+/// # Name: 3024 (dark)
+/// # Author: Chris Kempson
+/// ```
+///
+/// The name is left out, as it rarely differs from the file name in more than case.
+fn parse_header_comments(content: &str) -> Metadata {
+	let mut metadata = Metadata::default();
+	let header = content.lines().map_while(|line| line.trim().strip_prefix('#'));
+	for (key, value) in header.filter_map(|comment| comment.split_once(':')) {
+		let value = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+		match key.trim().to_lowercase().as_str() {
+			"author" => metadata.author = value,
+			"url" | "source" => metadata.url = value,
+			"license" => metadata.license = value,
+			_ => {},
+		}
+	}
+	metadata
+}
+
+/// Opens a store with `files`, given as name and content, as the user's themes.
+#[cfg(test)]
+pub fn open_test_store(files: &[(&str, &str)]) -> ThemeStore {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+	let dir = std::env::temp_dir().join(format!(
+		"alacritty_themes-test-{}-{}",
+		std::process::id(),
+		COUNT.fetch_add(1, Ordering::Relaxed)
+	));
+	fs::create_dir_all(&dir).unwrap();
+	for (name, content) in files {
+		let path = dir.join(format!("{name}.toml"));
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, content).unwrap();
+	}
+	let store = ThemeStore::open_layers(&[Layer {
+		origin: Origin::User,
+		path: dir.clone(),
+	}]);
+	fs::remove_dir_all(&dir).unwrap();
+	store.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const THEME: &str = "[colors.primary]\nbackground = \"#000000\"\nforeground = \"#ffffff\"\n";
+
+	fn load(store: &ThemeStore, name: &str) -> Result<Colors> {
+		store.load(store.get(name)?)
+	}
+
+	#[test]
+	fn child_overrides_parent() {
+		let store = open_test_store(&[
+			("Parent", THEME),
+			(
+				"Child",
+				"extends = \"Parent\"\n[colors.primary]\nbackground = \"#101010\"\n",
+			),
+		]);
+		let primary = load(&store, "Child").unwrap().primary.unwrap();
+		assert_eq!(primary.background.as_deref(), Some("#101010"));
+		assert_eq!(primary.foreground.as_deref(), Some("#ffffff"));
+	}
+
+	#[test]
+	fn broken_parent_is_named() {
+		let store = open_test_store(&[
+			("Parent", "[colors.primary]\nbackground = \"oops\"\n"),
+			("Child", "extends = \"Parent\""),
+		]);
+		let err = load(&store, "Child").unwrap_err().to_string();
+		assert!(err.starts_with("'Child' extends 'Parent': "), "{err}");
+		assert!(err.contains("oops"), "{err}");
+
+		let store = open_test_store(&[("Child", "extends = \"Nowhere\"")]);
+		let err = load(&store, "Child").unwrap_err().to_string();
+		assert_eq!(err, "'Child' extends unknown theme 'Nowhere'");
+	}
+
+	#[test]
+	fn invalid_metadata_leaves_colors_loadable() {
+		let content = format!("# Author: Jane\n{THEME}[metadata]\nauthor = 42\nvariant = \"dim\"\n");
+		let store = open_test_store(&[("Odd", &content), ("Fine", THEME)]);
+		let entry = store.get("Odd").unwrap();
+		assert!(store.load(entry).is_ok());
+		let err = entry.metadata_error().unwrap();
+		assert!(err.starts_with("Invalid metadata: "), "{err}");
+		// What the header comments say is still known.
+		assert_eq!(entry.metadata().unwrap().author.as_deref(), Some("Jane"));
+		assert_eq!(store.get("Fine").unwrap().metadata_error(), None);
+	}
+
+	#[test]
+	fn symlink_loops_and_unreadable_layers_are_skipped() {
+		let dir = std::env::temp_dir().join(format!("alacritty_themes-test-loop-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(dir.join("retro")).unwrap();
+		fs::write(dir.join("retro/Amber.toml"), THEME).unwrap();
+		std::os::unix::fs::symlink(&dir, dir.join("retro/loop")).unwrap();
+		fs::write(dir.join("file"), "").unwrap();
+
+		let layer = |path: PathBuf| Layer {
+			origin: Origin::User,
+			path,
+		};
+		let store = ThemeStore::open_layers(&[layer(dir.join("file")), layer(dir.join("missing")), layer(dir.clone())]);
+		fs::remove_dir_all(&dir).unwrap();
+
+		let store = store.unwrap();
+		assert!(store.find("retro/Amber").is_some());
+		assert!(!store.entries().iter().any(|entry| entry.name.contains("loop")));
+		assert_eq!(store.warnings().len(), 1, "{:?}", store.warnings());
+		assert!(store.warnings()[0].starts_with(&format!("Skipped {}: ", dir.join("file").display())));
+	}
+
+	#[test]
+	fn cycle_is_reported() {
+		let store = open_test_store(&[
+			("A", "extends = \"B\""),
+			("B", "extends = \"C\""),
+			("C", "extends = \"A\""),
+		]);
+		let err = load(&store, "A").unwrap_err().to_string();
+		assert_eq!(
+			err,
+			"'A' extends 'B': 'B' extends 'C': Themes extend each other in a cycle: A -> B -> C -> A"
+		);
+	}
+}
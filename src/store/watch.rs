@@ -0,0 +1,155 @@
+//! Notices changes to the themes directories of a [`ThemeStore`](super::ThemeStore), so that the
+//! theme list can follow edits made in another program.
+
+use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+use super::Layer;
+use crate::Result;
+
+pub struct ThemeWatcher {
+	// Watching stops when the watcher is dropped.
+	watcher: RecommendedWatcher,
+	events: Receiver<notify::Result<Event>>,
+	/// Layer directories watched with their subdirectories.
+	watched: Vec<PathBuf>,
+	/// Layer directories that don't exist yet.
+	missing: Vec<PathBuf>,
+	/// The nearest existing ancestors of the missing layers, watched for the layers to appear.
+	ancestors: Vec<PathBuf>,
+}
+
+impl ThemeWatcher {
+	/// Watches the directories of `layers`, including their subdirectories. Those that don't exist
+	/// yet are watched for from their nearest existing ancestor.
+	pub fn new(layers: &[Layer]) -> Result<Self> {
+		let (sender, events) = channel();
+		let mut watcher = Self {
+			watcher: recommended_watcher(sender)?,
+			events,
+			watched: Vec::new(),
+			missing: layers.iter().map(|layer| layer.path.clone()).collect(),
+			ancestors: Vec::new(),
+		};
+		watcher.watch_missing();
+		Ok(watcher)
+	}
+
+	/// Whether any theme changed since the last call, including by a layer directory appearing.
+	/// Never blocks.
+	pub fn changed(&mut self) -> bool {
+		let mut changed = false;
+		let mut created = false;
+		while let Ok(event) = self.events.try_recv() {
+			let Ok(event) = event else { continue };
+			created |= event
+				.paths
+				.iter()
+				.any(|path| self.missing.iter().any(|layer| layer.starts_with(path)));
+			changed |= affects_themes(&event)
+				&& event
+					.paths
+					.iter()
+					.any(|path| self.watched.iter().any(|layer| path.starts_with(layer)));
+		}
+		if created {
+			changed |= self.watch_missing();
+		}
+		changed
+	}
+
+	/// Starts watching the missing layers that exist by now, and watches the nearest existing
+	/// ancestors of the others in place of the previous ones. Whether any layer appeared.
+	///
+	/// Directories that can't be watched are left out, so only changes there go unnoticed.
+	fn watch_missing(&mut self) -> bool {
+		let mut appeared = false;
+		let mut ancestors: Vec<PathBuf> = Vec::new();
+		for layer in mem::take(&mut self.missing) {
+			if layer.is_dir() && self.watcher.watch(&layer, RecursiveMode::Recursive).is_ok() {
+				self.watched.push(layer);
+				appeared = true;
+				continue;
+			}
+			let ancestor = layer.ancestors().skip(1).find(|path| path.is_dir());
+			// Ancestors inside a watched layer are watched already.
+			if let Some(ancestor) = ancestor.filter(|ancestor| {
+				!ancestors.iter().any(|known| known == ancestor)
+					&& !self.watched.iter().any(|watched| ancestor.starts_with(watched))
+			}) {
+				ancestors.push(ancestor.to_path_buf());
+			}
+			self.missing.push(layer);
+		}
+
+		for ancestor in self.ancestors.iter().filter(|ancestor| !ancestors.contains(ancestor)) {
+			// The directory may be gone, which ends its watch anyway.
+			let _ = self.watcher.unwatch(ancestor);
+		}
+		ancestors.retain(|ancestor| {
+			self.ancestors.contains(ancestor) || self.watcher.watch(ancestor, RecursiveMode::NonRecursive).is_ok()
+		});
+		self.ancestors = ancestors;
+		appeared
+	}
+}
+
+fn affects_themes(event: &Event) -> bool {
+	if matches!(event.kind, EventKind::Access(_)) {
+		return false;
+	}
+	// Paths without an extension are most likely directories, which may hold themes.
+	event
+		.paths
+		.iter()
+		.any(|path| path.extension().is_none_or(|extension| extension == "toml") && !is_hidden(path))
+}
+
+fn is_hidden(path: &Path) -> bool {
+	path.file_name()
+		.is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::store::Origin;
+	use std::fs;
+	use std::thread::sleep;
+	use std::time::Duration;
+
+	/// Whether `watcher` reports a change within `tries` polls.
+	fn changes(watcher: &mut ThemeWatcher, tries: usize) -> bool {
+		(0..tries).any(|_| {
+			sleep(Duration::from_millis(20));
+			watcher.changed()
+		})
+	}
+
+	#[test]
+	fn notices_layers_created_later() {
+		let dir = std::env::temp_dir().join(format!("alacritty_themes-test-watch-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		let layer = dir.join("config/alacritty_themes");
+		let mut watcher = ThemeWatcher::new(&[Layer {
+			origin: Origin::User,
+			path: layer.clone(),
+		}])
+		.unwrap();
+
+		// Other files next to the layer are no change.
+		fs::create_dir(dir.join("config")).unwrap();
+		fs::write(dir.join("config/other"), "").unwrap();
+		assert!(!changes(&mut watcher, 10));
+
+		fs::create_dir(&layer).unwrap();
+		assert!(changes(&mut watcher, 100));
+		fs::write(layer.join("Amber.toml"), "").unwrap();
+		assert!(changes(&mut watcher, 100));
+
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
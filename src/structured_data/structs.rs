@@ -1,85 +1,255 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct Colors {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub bright: Option<ColorScheme>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub cursor: Option<CursorColors>,
 	#[serde(skip_serializing_if = "Option::is_none")]
+	pub dim: Option<ColorScheme>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub hints: Option<HintColors>,
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub normal: Option<ColorScheme>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub primary: Option<PrimaryColors>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub search: Option<SearchColors>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub selection: Option<SelectionColors>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub vi_mode_cursor: Option<CursorColors>,
+}
+
+/// The optional `[metadata]` table of a theme file. It is never written to `alacritty.toml`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct Metadata {
+	/// Display name, shown instead of the prettified file name.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub author: Option<String>,
+	/// Where the theme comes from upstream.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub url: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub license: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub variant: Option<Variant>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<String>,
+	/// Name of the light/dark counterpart of this theme.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub pair: Option<String>,
+}
+
+impl Metadata {
+	/// Fills in whatever is missing from `fallback`.
+	pub fn or(self, fallback: Self) -> Self {
+		Self {
+			name: self.name.or(fallback.name),
+			author: self.author.or(fallback.author),
+			url: self.url.or(fallback.url),
+			license: self.license.or(fallback.license),
+			variant: self.variant.or(fallback.variant),
+			tags: if self.tags.is_empty() { fallback.tags } else { self.tags },
+			pair: self.pair.or(fallback.pair),
+		}
+	}
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+	Dark,
+	Light,
+}
+
+impl Variant {
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Dark => "dark",
+			Self::Light => "light",
+		}
+	}
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct CursorColors {
 	#[serde(skip_serializing_if = "Option::is_none")]
-	cursor: Option<String>,
+	pub cursor: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	text: Option<String>,
+	pub text: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct ColorScheme {
 	#[serde(skip_serializing_if = "Option::is_none")]
-	black: Option<String>,
+	pub black: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	blue: Option<String>,
+	pub blue: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	cyan: Option<String>,
+	pub cyan: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	green: Option<String>,
+	pub green: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	magenta: Option<String>,
+	pub magenta: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	red: Option<String>,
+	pub red: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	white: Option<String>,
+	pub white: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	yellow: Option<String>,
+	pub yellow: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PrimaryColors {
 	#[serde(skip_serializing_if = "Option::is_none")]
-	background: Option<String>,
+	pub background: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub foreground: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub dim_foreground: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub bright_foreground: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SelectionColors {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub text: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	foreground: Option<String>,
+	pub background: Option<String>,
+}
+
+/// The colors of a cell, such as a search match or a hint label.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CellColors {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub foreground: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub background: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SearchColors {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub matches: Option<CellColors>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub focused_match: Option<CellColors>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct HintColors {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub start: Option<CellColors>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub end: Option<CellColors>,
+}
+
+/// The colors `merge_colors(default, custom)` takes from `default`, as dotted paths such as
+/// `cursor.text`.
+pub fn inherited_fields(default: &Colors, custom: &Colors) -> Vec<String> {
+	fn walk(prefix: &str, default: &toml::Value, custom: Option<&toml::Value>, fields: &mut Vec<String>) {
+		let Some(table) = default.as_table() else {
+			if custom.is_none() {
+				fields.push(prefix.to_string());
+			}
+			return;
+		};
+		for (key, value) in table {
+			let path = if prefix.is_empty() {
+				key.clone()
+			} else {
+				format!("{prefix}.{key}")
+			};
+			walk(&path, value, custom.and_then(|custom| custom.get(key)), fields);
+		}
+	}
+
+	let mut fields = Vec::new();
+	if let (Ok(default), Ok(custom)) = (toml::Value::try_from(default), toml::Value::try_from(custom)) {
+		walk("", &default, Some(&custom), &mut fields);
+	}
+	fields
+}
+
+/// Every color set in `colors`, by dotted path such as `cursor.text`.
+pub fn color_fields(colors: &Colors) -> Vec<(String, String)> {
+	fn walk(prefix: &str, value: &toml::Value, fields: &mut Vec<(String, String)>) {
+		match value {
+			toml::Value::Table(table) => {
+				for (key, value) in table {
+					let path = if prefix.is_empty() {
+						key.clone()
+					} else {
+						format!("{prefix}.{key}")
+					};
+					walk(&path, value, fields);
+				}
+			},
+			toml::Value::String(color) => fields.push((prefix.to_string(), color.clone())),
+			_ => {},
+		}
+	}
+
+	let mut fields = Vec::new();
+	if let Ok(colors) = toml::Value::try_from(colors) {
+		walk("", &colors, &mut fields);
+	}
+	fields
 }
 
 pub fn merge_colors(default: &Colors, custom: &Colors) -> Colors {
 	Colors {
-		bright: match (&custom.bright, &default.bright) {
-			(Some(c), Some(d)) => Some(merge_color_scheme(c, d)),
-			(Some(c), None) => Some(c.clone()),
-			(None, Some(d)) => Some(d.clone()),
-			(None, None) => None,
-		},
-		cursor: match (&custom.cursor, &default.cursor) {
-			(Some(c), Some(d)) => Some(CursorColors {
-				cursor: c.cursor.clone().or_else(|| d.cursor.clone()),
-				text: c.text.clone().or_else(|| d.text.clone()),
-			}),
-			(Some(c), None) => Some(c.clone()),
-			(None, Some(d)) => Some(d.clone()),
-			(None, None) => None,
-		},
-		normal: match (&custom.normal, &default.normal) {
-			(Some(c), Some(d)) => Some(merge_color_scheme(c, d)),
-			(Some(c), None) => Some(c.clone()),
-			(None, Some(d)) => Some(d.clone()),
-			(None, None) => None,
-		},
-		primary: match (&custom.primary, &default.primary) {
-			(Some(c), Some(d)) => Some(PrimaryColors {
-				background: c.background.clone().or_else(|| d.background.clone()),
-				foreground: c.foreground.clone().or_else(|| d.foreground.clone()),
-			}),
-			(Some(c), None) => Some(c.clone()),
-			(None, Some(d)) => Some(d.clone()),
-			(None, None) => None,
-		},
+		bright: merge(&custom.bright, &default.bright, merge_color_scheme),
+		cursor: merge(&custom.cursor, &default.cursor, merge_cursor),
+		dim: merge(&custom.dim, &default.dim, merge_color_scheme),
+		hints: merge(&custom.hints, &default.hints, |c, d| HintColors {
+			start: merge(&c.start, &d.start, merge_cell),
+			end: merge(&c.end, &d.end, merge_cell),
+		}),
+		normal: merge(&custom.normal, &default.normal, merge_color_scheme),
+		primary: merge(&custom.primary, &default.primary, |c, d| PrimaryColors {
+			background: c.background.clone().or_else(|| d.background.clone()),
+			foreground: c.foreground.clone().or_else(|| d.foreground.clone()),
+			dim_foreground: c.dim_foreground.clone().or_else(|| d.dim_foreground.clone()),
+			bright_foreground: c.bright_foreground.clone().or_else(|| d.bright_foreground.clone()),
+		}),
+		search: merge(&custom.search, &default.search, |c, d| SearchColors {
+			matches: merge(&c.matches, &d.matches, merge_cell),
+			focused_match: merge(&c.focused_match, &d.focused_match, merge_cell),
+		}),
+		selection: merge(&custom.selection, &default.selection, |c, d| SelectionColors {
+			text: c.text.clone().or_else(|| d.text.clone()),
+			background: c.background.clone().or_else(|| d.background.clone()),
+		}),
+		vi_mode_cursor: merge(&custom.vi_mode_cursor, &default.vi_mode_cursor, merge_cursor),
+	}
+}
+
+/// `custom` with what it lacks taken from `default`, merging the two with `both` if both are set.
+fn merge<T: Clone>(custom: &Option<T>, default: &Option<T>, both: impl Fn(&T, &T) -> T) -> Option<T> {
+	match (custom, default) {
+		(Some(c), Some(d)) => Some(both(c, d)),
+		(Some(c), None) => Some(c.clone()),
+		(None, Some(d)) => Some(d.clone()),
+		(None, None) => None,
+	}
+}
+
+fn merge_cursor(custom: &CursorColors, default: &CursorColors) -> CursorColors {
+	CursorColors {
+		cursor: custom.cursor.clone().or_else(|| default.cursor.clone()),
+		text: custom.text.clone().or_else(|| default.text.clone()),
+	}
+}
+
+fn merge_cell(custom: &CellColors, default: &CellColors) -> CellColors {
+	CellColors {
+		foreground: custom.foreground.clone().or_else(|| default.foreground.clone()),
+		background: custom.background.clone().or_else(|| default.background.clone()),
 	}
 }
 
@@ -95,3 +265,29 @@ fn merge_color_scheme(custom: &ColorScheme, default: &ColorScheme) -> ColorSchem
 		yellow: custom.yellow.clone().or_else(|| default.yellow.clone()),
 	}
 }
+
+impl Colors {
+	pub fn background(&self) -> Option<&str> {
+		self.primary.as_ref()?.background.as_deref()
+	}
+
+	pub fn foreground(&self) -> Option<&str> {
+		self.primary.as_ref()?.foreground.as_deref()
+	}
+}
+
+impl ColorScheme {
+	/// The eight colors by name, in ANSI order.
+	pub fn slots(&self) -> [(&'static str, Option<&str>); 8] {
+		[
+			("black", self.black.as_deref()),
+			("red", self.red.as_deref()),
+			("green", self.green.as_deref()),
+			("yellow", self.yellow.as_deref()),
+			("blue", self.blue.as_deref()),
+			("magenta", self.magenta.as_deref()),
+			("cyan", self.cyan.as_deref()),
+			("white", self.white.as_deref()),
+		]
+	}
+}
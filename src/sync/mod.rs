@@ -0,0 +1,269 @@
+//! Keeps the user's themes directory in step with the themes bundled into the binary without
+//! clobbering local edits. A manifest in the XDG state directory records the checksum of every
+//! bundled file as it was last installed, which tells unmodified files apart from edited ones.
+//! Copies installed before there was a manifest are recognized by the checksums of every released
+//! version of the bundled themes.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::state::{load_state_file, save_state_file};
+use crate::{Result, THEMES_DIR};
+
+const MANIFEST_FILE: &str = "manifest.toml";
+/// Lines of `<checksum>  <file name>`.
+const RELEASED: &str = include_str!("released.txt");
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(default)]
+struct Manifest {
+	/// File name mapped to the SHA-256 of the bundled content that was installed.
+	files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+	fn load() -> Result<Self> {
+		load_state_file(MANIFEST_FILE)
+	}
+
+	fn save(&self) -> Result<()> {
+		save_state_file(MANIFEST_FILE, self)
+	}
+}
+
+/// What happened (or, for a dry run, would happen) to the bundled themes.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+	/// Bundled themes that were not present yet.
+	pub installed: Vec<String>,
+	/// Bundled themes whose local copy was unmodified and out of date.
+	pub updated: Vec<String>,
+	/// Bundled themes whose local copy differs from anything that was installed, left untouched.
+	pub conflicts: Vec<String>,
+	/// Bundled themes the user deleted after they were installed, left deleted.
+	pub removed: Vec<String>,
+	pub unchanged: usize,
+}
+
+fn checksum(content: &[u8]) -> String {
+	format!("{:x}", Sha256::digest(content))
+}
+
+/// The bundled themes as they were released, as `(file name, checksum)`.
+fn released() -> HashSet<(&'static str, &'static str)> {
+	RELEASED
+		.lines()
+		.filter(|line| !line.starts_with('#'))
+		.filter_map(|line| line.split_once("  "))
+		.map(|(checksum, name)| (name, checksum))
+		.collect()
+}
+
+/// Installs new bundled themes and updates unmodified ones in `themes_path`. With `dry_run`
+/// nothing is written and the report describes what would change.
+pub fn sync_themes(themes_path: &Path, dry_run: bool) -> Result<SyncReport> {
+	// Without a themes directory there is nothing to protect, whatever the manifest says.
+	let mut manifest = if themes_path.exists() {
+		Manifest::load()?
+	} else {
+		Manifest::default()
+	};
+	let bundled: Vec<(String, &[u8])> = THEMES_DIR
+		.files()
+		.map(|file| (file.path().to_string_lossy().into_owned(), file.contents()))
+		.collect();
+
+	let report = sync_files(&bundled, &released(), &mut manifest, themes_path, dry_run)?;
+	if !dry_run {
+		manifest.save()?;
+	}
+	Ok(report)
+}
+
+fn sync_files(
+	bundled: &[(String, &[u8])],
+	released: &HashSet<(&str, &str)>,
+	manifest: &mut Manifest,
+	themes_path: &Path,
+	dry_run: bool,
+) -> Result<SyncReport> {
+	let mut report = SyncReport::default();
+	if !dry_run {
+		fs::create_dir_all(themes_path)?;
+	}
+
+	for (name, contents) in bundled {
+		let name = name.clone();
+		let bundled = checksum(contents);
+		let installed = manifest.files.get(&name);
+
+		let local = match fs::read(themes_path.join(&name)) {
+			Ok(content) => Some(checksum(&content)),
+			Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+			Err(err) => return Err(err.into()),
+		};
+
+		let write = match (local, installed) {
+			(None, Some(_)) => {
+				report.removed.push(name);
+				continue;
+			},
+			(None, None) => {
+				report.installed.push(name.clone());
+				true
+			},
+			(Some(local), _) if local == bundled => {
+				report.unchanged += 1;
+				false
+			},
+			(Some(local), Some(installed)) if local == *installed => {
+				report.updated.push(name.clone());
+				true
+			},
+			// Installed before there was a manifest, and not edited since.
+			(Some(local), None) if released.contains(&(name.as_str(), local.as_str())) => {
+				report.updated.push(name.clone());
+				true
+			},
+			(Some(_), _) => {
+				report.conflicts.push(name);
+				continue;
+			},
+		};
+
+		if write && !dry_run {
+			fs::write(themes_path.join(&name), contents)?;
+		}
+		manifest.files.insert(name, bundled);
+	}
+
+	Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::PathBuf;
+
+	struct TempDir(PathBuf);
+
+	impl TempDir {
+		fn new(name: &str) -> Self {
+			let path = std::env::temp_dir().join(format!("alacritty_themes-sync-{name}-{}", std::process::id()));
+			let _ = fs::remove_dir_all(&path);
+			Self(path)
+		}
+
+		fn write(&self, name: &str, content: &str) {
+			fs::create_dir_all(&self.0).unwrap();
+			fs::write(self.0.join(name), content).unwrap();
+		}
+
+		fn read(&self, name: &str) -> Option<String> {
+			fs::read_to_string(self.0.join(name)).ok()
+		}
+	}
+
+	impl Drop for TempDir {
+		fn drop(&mut self) {
+			let _ = fs::remove_dir_all(&self.0);
+		}
+	}
+
+	fn bundled(files: &[(&str, &'static str)]) -> Vec<(String, &'static [u8])> {
+		files
+			.iter()
+			.map(|(name, content)| (name.to_string(), content.as_bytes()))
+			.collect()
+	}
+
+	fn manifest(files: &[(&str, &str)]) -> Manifest {
+		Manifest {
+			files: files
+				.iter()
+				.map(|(name, content)| (name.to_string(), checksum(content.as_bytes())))
+				.collect(),
+		}
+	}
+
+	#[test]
+	fn installs_updates_and_keeps_edits() {
+		let dir = TempDir::new("paths");
+		dir.write("Unchanged.toml", "same");
+		dir.write("Updated.toml", "old");
+		dir.write("Edited.toml", "mine");
+		let bundled = bundled(&[
+			("New.toml", "new"),
+			("Unchanged.toml", "same"),
+			("Updated.toml", "newer"),
+			("Edited.toml", "newer"),
+			("Deleted.toml", "newer"),
+		]);
+		let mut manifest = manifest(&[
+			("Unchanged.toml", "same"),
+			("Updated.toml", "old"),
+			("Edited.toml", "old"),
+			("Deleted.toml", "old"),
+		]);
+
+		let report = sync_files(&bundled, &HashSet::new(), &mut manifest, &dir.0, false).unwrap();
+		assert_eq!(report.installed, ["New.toml"]);
+		assert_eq!(report.updated, ["Updated.toml"]);
+		assert_eq!(report.conflicts, ["Edited.toml"]);
+		assert_eq!(report.removed, ["Deleted.toml"]);
+		assert_eq!(report.unchanged, 1);
+
+		assert_eq!(dir.read("New.toml").as_deref(), Some("new"));
+		assert_eq!(dir.read("Updated.toml").as_deref(), Some("newer"));
+		assert_eq!(dir.read("Edited.toml").as_deref(), Some("mine"));
+		assert_eq!(dir.read("Deleted.toml"), None);
+		assert_eq!(manifest.files["Updated.toml"], checksum(b"newer"));
+		assert_eq!(manifest.files["Edited.toml"], checksum(b"old"));
+	}
+
+	#[test]
+	fn updates_copies_from_before_the_manifest() {
+		let dir = TempDir::new("released");
+		dir.write("Pristine.toml", "old");
+		dir.write("Edited.toml", "mine");
+		let bundled = bundled(&[("Pristine.toml", "new"), ("Edited.toml", "new")]);
+		let old = checksum(b"old");
+		let released = HashSet::from([("Pristine.toml", old.as_str()), ("Edited.toml", old.as_str())]);
+
+		let report = sync_files(&bundled, &released, &mut Manifest::default(), &dir.0, false).unwrap();
+		assert_eq!(report.updated, ["Pristine.toml"]);
+		assert_eq!(report.conflicts, ["Edited.toml"]);
+		assert_eq!(dir.read("Pristine.toml").as_deref(), Some("new"));
+		assert_eq!(dir.read("Edited.toml").as_deref(), Some("mine"));
+	}
+
+	#[test]
+	fn dry_run_writes_nothing() {
+		let dir = TempDir::new("dry-run");
+		dir.write("Updated.toml", "old");
+		let bundled = bundled(&[("New.toml", "new"), ("Updated.toml", "new")]);
+		let mut manifest = manifest(&[("Updated.toml", "old")]);
+
+		let report = sync_files(&bundled, &HashSet::new(), &mut manifest, &dir.0, true).unwrap();
+		assert_eq!(report.installed, ["New.toml"]);
+		assert_eq!(report.updated, ["Updated.toml"]);
+		assert_eq!(dir.read("New.toml"), None);
+		assert_eq!(dir.read("Updated.toml").as_deref(), Some("old"));
+	}
+
+	#[test]
+	fn released_checksums_cover_bundled_themes() {
+		let released = released();
+		for file in THEMES_DIR.files() {
+			let name = file.path().to_string_lossy();
+			let checksum = checksum(file.contents());
+			assert!(
+				released.contains(&(name.as_ref(), checksum.as_str())),
+				"{name} is missing from released.txt"
+			);
+		}
+	}
+}
@@ -0,0 +1,292 @@
+//! Turns captured terminal output into styled text. The 16 ANSI colors become named colors, which
+//! stand for those of the previewed theme, while 256-color and 24-bit colors are kept as they are.
+//! Escape sequences other than SGR, such as cursor movement, are dropped.
+
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans, Text};
+
+use crate::utils::example::ANSI_COLORS;
+
+const ESC: char = '\x1b';
+const TAB_WIDTH: usize = 8;
+
+pub fn parse_ansi(input: &str) -> Text<'static> {
+	let mut parser = Parser::default();
+	let mut chars = input.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			ESC => match chars.peek() {
+				Some('[') => {
+					chars.next();
+					// Parameters and intermediates, up to the final byte of the sequence.
+					let mut sequence = String::new();
+					for c in chars.by_ref() {
+						if ('\x40'..='\x7e').contains(&c) {
+							if c == 'm' {
+								parser.select_graphic_rendition(&sequence);
+							}
+							break;
+						}
+						sequence.push(c);
+					}
+				},
+				Some(']' | 'P' | '_' | '^') => {
+					// A string, up to BEL or the string terminator `ESC \`.
+					chars.next();
+					while let Some(c) = chars.next() {
+						if c == '\x07' || c == ESC && chars.next_if_eq(&'\\').is_some() {
+							break;
+						}
+					}
+				},
+				// A lone ESC before another sequence.
+				Some(&ESC) | None => {},
+				// Anything else ends at its final byte, after any intermediates as in `ESC ( B`.
+				Some(_) => {
+					while chars.next_if(|c| (' '..='/').contains(c)).is_some() {}
+					chars.next();
+				},
+			},
+			'\n' => parser.new_line(),
+			'\t' => {
+				let spaces = TAB_WIDTH - parser.width % TAB_WIDTH;
+				parser.push_str(&" ".repeat(spaces));
+			},
+			c if c.is_control() => {},
+			c => parser.push_str(c.encode_utf8(&mut [0; 4])),
+		}
+	}
+	parser.finish()
+}
+
+#[derive(Default)]
+struct Parser {
+	lines: Vec<Spans<'static>>,
+	spans: Vec<Span<'static>>,
+	text: String,
+	style: Style,
+	/// Width of the current line so far, for tab stops.
+	width: usize,
+}
+
+impl Parser {
+	fn push_str(&mut self, text: &str) {
+		self.text.push_str(text);
+		self.width += text.chars().count();
+	}
+
+	fn flush(&mut self) {
+		if !self.text.is_empty() {
+			self.spans
+				.push(Span::styled(std::mem::take(&mut self.text), self.style));
+		}
+	}
+
+	fn new_line(&mut self) {
+		self.flush();
+		self.lines.push(Spans::from(std::mem::take(&mut self.spans)));
+		self.width = 0;
+	}
+
+	fn finish(mut self) -> Text<'static> {
+		self.flush();
+		if !self.spans.is_empty() {
+			self.lines.push(Spans::from(self.spans));
+		}
+		Text::from(self.lines)
+	}
+
+	/// Applies the parameters of an SGR sequence (`ESC [ ... m`).
+	fn select_graphic_rendition(&mut self, sequence: &str) {
+		self.flush();
+		// Colors may also be given with colons, as in `38:2::255:0:0`.
+		let mut params = sequence.split(';');
+		while let Some(param) = params.next() {
+			let mut subparams = param.split(':').map(|value| value.parse::<u16>().ok());
+			let code = subparams.next().flatten().unwrap_or(0);
+			let color = match code {
+				38 | 48 if param.contains(':') => extended_color(&subparams.collect::<Vec<_>>()),
+				38 | 48 => {
+					let kind = params.next().and_then(|value| value.parse().ok());
+					let count = match kind {
+						Some(5) => 1,
+						Some(2) => 3,
+						_ => 0,
+					};
+					let values: Vec<_> = std::iter::once(kind)
+						.chain(params.by_ref().take(count).map(|value| value.parse().ok()))
+						.collect();
+					extended_color(&values)
+				},
+				_ => None,
+			};
+
+			let style = &mut self.style;
+			match code {
+				0 => *style = Style::default(),
+				1 => *style = style.add_modifier(Modifier::BOLD),
+				2 => *style = style.add_modifier(Modifier::DIM),
+				3 => *style = style.add_modifier(Modifier::ITALIC),
+				4 => *style = style.add_modifier(Modifier::UNDERLINED),
+				5 => *style = style.add_modifier(Modifier::SLOW_BLINK),
+				6 => *style = style.add_modifier(Modifier::RAPID_BLINK),
+				7 => *style = style.add_modifier(Modifier::REVERSED),
+				8 => *style = style.add_modifier(Modifier::HIDDEN),
+				9 => *style = style.add_modifier(Modifier::CROSSED_OUT),
+				22 => style.add_modifier.remove(Modifier::BOLD | Modifier::DIM),
+				23 => style.add_modifier.remove(Modifier::ITALIC),
+				24 => style.add_modifier.remove(Modifier::UNDERLINED),
+				25 => style.add_modifier.remove(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+				27 => style.add_modifier.remove(Modifier::REVERSED),
+				28 => style.add_modifier.remove(Modifier::HIDDEN),
+				29 => style.add_modifier.remove(Modifier::CROSSED_OUT),
+				30..=37 => style.fg = Some(ANSI_COLORS[usize::from(code - 30)]),
+				38 => style.fg = color.or(style.fg),
+				39 => style.fg = None,
+				40..=47 => style.bg = Some(ANSI_COLORS[usize::from(code - 40)]),
+				48 => style.bg = color.or(style.bg),
+				49 => style.bg = None,
+				90..=97 => style.fg = Some(ANSI_COLORS[usize::from(code - 90 + 8)]),
+				100..=107 => style.bg = Some(ANSI_COLORS[usize::from(code - 100 + 8)]),
+				_ => {},
+			}
+		}
+	}
+}
+
+/// The color of a `38` or `48` parameter, from the values that follow it: `5, n` for one of 256
+/// colors or `2, r, g, b` for a 24-bit color. Given with colons, a color space may come before the
+/// components.
+fn extended_color(values: &[Option<u16>]) -> Option<Color> {
+	let byte = |value: Option<u16>| value.and_then(|value| u8::try_from(value).ok());
+	match values.first().copied().flatten()? {
+		5 => byte(values.get(1).copied().flatten()).map(indexed_color),
+		2 if values.len() >= 4 => match values[values.len() - 3..] {
+			[r, g, b] => Some(Color::Rgb(byte(r)?, byte(g)?, byte(b)?)),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// One of the 256 colors: the 16 ANSI colors, then a 6×6×6 color cube and 24 grays.
+fn indexed_color(index: u8) -> Color {
+	const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+	match index {
+		0..=15 => ANSI_COLORS[usize::from(index)],
+		16..=231 => {
+			let cube = usize::from(index - 16);
+			Color::Rgb(LEVELS[cube / 36], LEVELS[cube / 6 % 6], LEVELS[cube % 6])
+		},
+		_ => {
+			let gray = 8 + (index - 232) * 10;
+			Color::Rgb(gray, gray, gray)
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The text and style of every span on each line.
+	fn spans(input: &str) -> Vec<Vec<(String, Style)>> {
+		parse_ansi(input)
+			.lines
+			.into_iter()
+			.map(|line| {
+				line.0
+					.into_iter()
+					.map(|span| (span.content.into_owned(), span.style))
+					.collect()
+			})
+			.collect()
+	}
+
+	fn fg(color: Color) -> Style {
+		Style::default().fg(color)
+	}
+
+	#[test]
+	fn plain_text() {
+		assert_eq!(
+			spans("one\ntwo"),
+			[
+				vec![("one".into(), Style::default())],
+				vec![("two".into(), Style::default())]
+			]
+		);
+		assert_eq!(spans("a\tb"), [vec![("a       b".into(), Style::default())]]);
+	}
+
+	#[test]
+	fn sixteen_colors() {
+		assert_eq!(
+			spans("\x1b[31mred\x1b[0m \x1b[92;44mon"),
+			[vec![
+				("red".into(), fg(Color::Red)),
+				(" ".into(), Style::default()),
+				("on".into(), fg(Color::LightGreen).bg(Color::Blue)),
+			]]
+		);
+		assert_eq!(
+			spans("\x1b[103mx"),
+			[vec![("x".into(), Style::default().bg(Color::LightYellow))]]
+		);
+	}
+
+	#[test]
+	fn indexed_colors() {
+		assert_eq!(
+			spans("\x1b[38;5;1ma\x1b[38;5;196mb\x1b[48;5;244mc"),
+			[vec![
+				("a".into(), fg(Color::Red)),
+				("b".into(), fg(Color::Rgb(255, 0, 0))),
+				("c".into(), fg(Color::Rgb(255, 0, 0)).bg(Color::Rgb(128, 128, 128))),
+			]]
+		);
+	}
+
+	#[test]
+	fn truecolor() {
+		assert_eq!(
+			spans("\x1b[38;2;1;2;3;1mx"),
+			[vec![("x".into(), fg(Color::Rgb(1, 2, 3)).add_modifier(Modifier::BOLD))]]
+		);
+		assert_eq!(spans("\x1b[48;2;300;2;3mx"), [vec![("x".into(), Style::default())]]);
+	}
+
+	#[test]
+	fn colon_forms() {
+		assert_eq!(
+			spans("\x1b[38:2::10:20:30ma\x1b[38:2:10:20:30mb\x1b[48:5:2mc"),
+			[vec![
+				("a".into(), fg(Color::Rgb(10, 20, 30))),
+				("b".into(), fg(Color::Rgb(10, 20, 30))),
+				("c".into(), fg(Color::Rgb(10, 20, 30)).bg(Color::Green)),
+			]]
+		);
+	}
+
+	#[test]
+	fn resets() {
+		assert_eq!(
+			spans("\x1b[1;2;3;31ma\x1b[22mb\x1b[23;39mc\x1b[mplain"),
+			[vec![
+				(
+					"a".into(),
+					fg(Color::Red).add_modifier(Modifier::BOLD | Modifier::DIM | Modifier::ITALIC)
+				),
+				("b".into(), fg(Color::Red).add_modifier(Modifier::ITALIC)),
+				("c".into(), Style::default()),
+				("plain".into(), Style::default()),
+			]]
+		);
+	}
+
+	#[test]
+	fn drops_other_sequences() {
+		let input = "\x1b]0;title\x07a\x1b]8;;https://example.com\x1b\\b\x1b[2Jc\x1b[?25l\x1b(Bd\r\n";
+		assert_eq!(spans(input), [vec![("abcd".into(), Style::default())]]);
+	}
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+
+use crate::structured_data::structs::{CellColors, Colors};
+
+pub type Rgb = (u8, u8, u8);
+
+/// How much Alacritty darkens colors for dim text when the theme has no dim colors.
+const DIM_FACTOR: f64 = 0.66;
+
+/// Cells that Alacritty draws in colors of their own rather than in those of their text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+	Cursor,
+	ViCursor,
+	Selection,
+	Match,
+	FocusedMatch,
+	HintStart,
+	HintEnd,
+}
+
+impl Highlight {
+	pub const ALL: [Self; 7] = [
+		Self::Cursor,
+		Self::ViCursor,
+		Self::Selection,
+		Self::Match,
+		Self::FocusedMatch,
+		Self::HintStart,
+		Self::HintEnd,
+	];
+
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Cursor => "cursor",
+			Self::ViCursor => "vi cursor",
+			Self::Selection => "selection",
+			Self::Match => "match",
+			Self::FocusedMatch => "focused",
+			Self::HintStart => "hint start",
+			Self::HintEnd => "hint end",
+		}
+	}
+
+	/// The text and background colors as the theme gives them, `None` if it leaves them unset.
+	pub fn given(self, colors: &Colors) -> Option<(Option<&str>, Option<&str>)> {
+		fn cell(cell: Option<&CellColors>) -> Option<(Option<&str>, Option<&str>)> {
+			cell.map(|cell| (cell.foreground.as_deref(), cell.background.as_deref()))
+		}
+
+		let search = colors.search.as_ref();
+		let hints = colors.hints.as_ref();
+		match self {
+			Self::Cursor => colors
+				.cursor
+				.as_ref()
+				.map(|cursor| (cursor.text.as_deref(), cursor.cursor.as_deref())),
+			Self::ViCursor => colors
+				.vi_mode_cursor
+				.as_ref()
+				.map(|cursor| (cursor.text.as_deref(), cursor.cursor.as_deref())),
+			Self::Selection => colors
+				.selection
+				.as_ref()
+				.map(|selection| (selection.text.as_deref(), selection.background.as_deref())),
+			Self::Match => cell(search.and_then(|search| search.matches.as_ref())),
+			Self::FocusedMatch => cell(search.and_then(|search| search.focused_match.as_ref())),
+			Self::HintStart => cell(hints.and_then(|hints| hints.start.as_ref())),
+			Self::HintEnd => cell(hints.and_then(|hints| hints.end.as_ref())),
+		}
+	}
+
+	/// Alacritty's own text and background colors for search matches and hints. The cursor and
+	/// selection have none, they invert the primary colors.
+	fn defaults(self) -> Option<(Rgb, Rgb)> {
+		const TEXT: Rgb = (0x18, 0x18, 0x18);
+		const RED: Rgb = (0xac, 0x42, 0x42);
+		const YELLOW: Rgb = (0xf4, 0xbf, 0x75);
+		match self {
+			Self::Match | Self::HintEnd => Some((TEXT, RED)),
+			Self::FocusedMatch | Self::HintStart => Some((TEXT, YELLOW)),
+			Self::Cursor | Self::ViCursor | Self::Selection => None,
+		}
+	}
+
+	/// The text and background colors of the cells, with Alacritty's defaults for those the theme
+	/// leaves unset.
+	pub fn colors(self, colors: &Colors) -> (Option<Rgb>, Option<Rgb>) {
+		let (text, background) = self.given(colors).unwrap_or_default();
+		let (default_text, default_background) = match self.defaults() {
+			Some((text, background)) => (Some(text), Some(background)),
+			None => (
+				colors.background().and_then(parse_hex),
+				colors.foreground().and_then(parse_hex),
+			),
+		};
+		(
+			cell_color(colors, text).or(default_text),
+			cell_color(colors, background).or(default_background),
+		)
+	}
+}
+
+/// Figures derived from a theme's colors.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Stats {
+	/// Relative luminance of the background.
+	pub luminance: f64,
+	/// Whether the background is darker than the foreground.
+	pub dark: bool,
+	/// Contrast ratio between background and foreground.
+	pub contrast: f64,
+	/// Saturation-weighted mean hue of the primary and normal colors in degrees, or `None` for
+	/// themes that are (nearly) gray.
+	pub hue: Option<f64>,
+}
+
+impl Stats {
+	/// `None` unless `colors` has a background and a foreground.
+	pub fn of(colors: &Colors) -> Option<Self> {
+		let background = colors.background().and_then(parse_hex)?;
+		let foreground = colors.foreground().and_then(parse_hex)?;
+
+		let normal = colors.normal.as_ref().map(|normal| normal.slots()).unwrap_or_default();
+		let palette = normal.iter().filter_map(|(_, color)| color.and_then(parse_hex));
+		let (mut x, mut y, mut count) = (0.0, 0.0, 0.0);
+		for color in [background, foreground].into_iter().chain(palette) {
+			let (hue, saturation, _) = hsl(color);
+			x += saturation * hue.to_radians().cos();
+			y += saturation * hue.to_radians().sin();
+			count += 1.0;
+		}
+		let hue = (x.hypot(y) / count >= 0.05).then(|| y.atan2(x).to_degrees().rem_euclid(360.0));
+
+		Some(Self {
+			luminance: relative_luminance(background),
+			dark: relative_luminance(background) < relative_luminance(foreground),
+			contrast: contrast_ratio(background, foreground),
+			hue,
+		})
+	}
+}
+
+/// Color `index` of the theme's 16-color palette: the normal colors, then the bright ones.
+pub fn palette_color(colors: &Colors, index: usize) -> Option<Rgb> {
+	let scheme = match index {
+		0..8 => colors.normal.as_ref(),
+		8..16 => colors.bright.as_ref(),
+		_ => None,
+	}?;
+	scheme.slots()[index % 8].1.and_then(parse_hex)
+}
+
+/// Color `index` of the dim colors, which Alacritty derives from the normal colors if the theme has
+/// none.
+pub fn dim_color(colors: &Colors, index: usize) -> Option<Rgb> {
+	colors
+		.dim
+		.as_ref()
+		.and_then(|dim| dim.slots().get(index)?.1)
+		.and_then(parse_hex)
+		.or_else(|| palette_color(colors, index).filter(|_| index < 8).map(dim))
+}
+
+/// The color of dim text without a color of its own.
+pub fn dim_foreground(colors: &Colors) -> Option<Rgb> {
+	colors
+		.primary
+		.as_ref()
+		.and_then(|primary| primary.dim_foreground.as_deref())
+		.and_then(parse_hex)
+		.or_else(|| colors.foreground().and_then(parse_hex).map(dim))
+}
+
+fn dim((r, g, b): Rgb) -> Rgb {
+	let scale = |channel: u8| (f64::from(channel) * DIM_FACTOR).round() as u8;
+	(scale(r), scale(g), scale(b))
+}
+
+/// A color given for cells, such as the cursor's, with `CellForeground` and `CellBackground` taken
+/// as the primary colors.
+pub fn cell_color(colors: &Colors, value: Option<&str>) -> Option<Rgb> {
+	match value? {
+		"CellForeground" => colors.foreground().and_then(parse_hex),
+		"CellBackground" => colors.background().and_then(parse_hex),
+		value => parse_hex(value),
+	}
+}
+
+/// Formats a color as `#rrggbb`.
+pub fn hex((r, g, b): Rgb) -> String {
+	format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Parses an Alacritty color string (`#rrggbb` or `0xrrggbb`) into its RGB components.
+pub fn parse_hex(value: &str) -> Option<Rgb> {
+	let hex = value
+		.trim()
+		.strip_prefix('#')
+		.or_else(|| value.trim().strip_prefix("0x"))?;
+	if hex.len() != 6 || !hex.is_ascii() {
+		return None;
+	}
+
+	let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+	Some((channel(0..2)?, channel(2..4)?, channel(4..6)?))
+}
+
+/// Relative luminance as defined by WCAG 2.x, in the range `0.0..=1.0`.
+pub fn relative_luminance((r, g, b): Rgb) -> f64 {
+	let linear = |channel: u8| {
+		let c = f64::from(channel) / 255.0;
+		if c <= 0.039_28 {
+			c / 12.92
+		} else {
+			((c + 0.055) / 1.055).powf(2.4)
+		}
+	};
+
+	0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// Hue in degrees, saturation and lightness, each of the latter in `0.0..=1.0`.
+pub fn hsl((r, g, b): Rgb) -> (f64, f64, f64) {
+	let (r, g, b) = (f64::from(r) / 255.0, f64::from(g) / 255.0, f64::from(b) / 255.0);
+	let max = r.max(g).max(b);
+	let min = r.min(g).min(b);
+	let lightness = (max + min) / 2.0;
+	let delta = max - min;
+	if delta == 0.0 {
+		return (0.0, 0.0, lightness);
+	}
+
+	let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+	let hue = if max == r {
+		((g - b) / delta).rem_euclid(6.0)
+	} else if max == g {
+		(b - r) / delta + 2.0
+	} else {
+		(r - g) / delta + 4.0
+	};
+	(hue * 60.0, saturation, lightness)
+}
+
+/// WCAG contrast ratio between two colors, in the range `1.0..=21.0`.
+pub fn contrast_ratio(a: Rgb, b: Rgb) -> f64 {
+	let (la, lb) = (relative_luminance(a), relative_luminance(b));
+	let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+	(lighter + 0.05) / (darker + 0.05)
+}
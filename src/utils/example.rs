@@ -1,10 +1,91 @@
-use tui::style::{Color, Style};
+use tui::style::{Color, Modifier, Style};
 use tui::text::{Span, Spans};
 use tui::widgets::Paragraph;
 
+use crate::structured_data::structs::Colors;
+use crate::utils::color::{dim_color, dim_foreground, palette_color, parse_hex, Rgb};
+use crate::utils::scenes::Scene;
+
+/// The named colors in ANSI order, the normal ones followed by the bright ones.
+pub const ANSI_COLORS: [Color; 16] = [
+	Color::Black,
+	Color::Red,
+	Color::Green,
+	Color::Yellow,
+	Color::Blue,
+	Color::Magenta,
+	Color::Cyan,
+	Color::Gray,
+	Color::DarkGray,
+	Color::LightRed,
+	Color::LightGreen,
+	Color::LightYellow,
+	Color::LightBlue,
+	Color::LightMagenta,
+	Color::LightCyan,
+	Color::White,
+];
+
+/// The preview `scene` in the theme's own `colors`, so that it is right before Alacritty has
+/// reloaded its config, or when Alacritty isn't the terminal the picker runs in.
+pub fn return_example(colors: &Colors, scene: &Scene) -> Paragraph<'static> {
+	let mut lines = scene.lines(colors);
+	// Scenes are written in named colors, which stand for those of the theme.
+	for span in lines.iter_mut().flat_map(|line| line.0.iter_mut()) {
+		span.style = themed_style(colors, span.style);
+	}
+	Paragraph::new(lines).style(primary_style(colors))
+}
+
+/// `style` with its named colors turned into the theme's. Dim text takes the theme's dim colors
+/// instead of being dimmed by the terminal, as Alacritty draws it.
+fn themed_style(colors: &Colors, mut style: Style) -> Style {
+	if style.add_modifier.contains(Modifier::DIM) {
+		let dimmed = match style.fg {
+			None => dim_foreground(colors),
+			Some(color) => ANSI_COLORS[..8]
+				.iter()
+				.position(|named| *named == color)
+				.and_then(|index| dim_color(colors, index)),
+		};
+		if let Some(dimmed) = dimmed {
+			style.fg = Some(rgb(dimmed));
+			style.add_modifier.remove(Modifier::DIM);
+		}
+	}
+	style.fg = style.fg.map(|color| themed(colors, color));
+	style.bg = style.bg.map(|color| themed(colors, color));
+	style
+}
+
+/// The primary colors of the theme as a style.
+pub fn primary_style(colors: &Colors) -> Style {
+	let mut style = Style::default();
+	if let Some(background) = colors.background().and_then(parse_hex) {
+		style = style.bg(rgb(background));
+	}
+	if let Some(foreground) = colors.foreground().and_then(parse_hex) {
+		style = style.fg(rgb(foreground));
+	}
+	style
+}
+
+/// The color of `colors` that a named color stands for, or the named color if the theme has none.
+pub fn themed(colors: &Colors, color: Color) -> Color {
+	ANSI_COLORS
+		.iter()
+		.position(|named| *named == color)
+		.and_then(|index| palette_color(colors, index))
+		.map_or(color, rgb)
+}
+
+pub fn rgb((r, g, b): Rgb) -> Color {
+	Color::Rgb(r, g, b)
+}
+
 #[allow(clippy::too_many_lines)]
-pub fn return_example() -> Paragraph<'static> {
-	Paragraph::new(vec![
+pub fn example_lines() -> Vec<Spans<'static>> {
+	vec![
 		// First line
 		Spans::from(vec![
 			Span::styled("use", Style::default().fg(Color::Red)),
@@ -169,5 +250,5 @@ pub fn return_example() -> Paragraph<'static> {
 		Spans::from(vec![Span::raw("    y")]),
 		// Closing brace
 		Spans::from(vec![Span::raw("}")]),
-	])
+	]
 }
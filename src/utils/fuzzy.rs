@@ -0,0 +1,105 @@
+/// Points for each matched character.
+const MATCH: i32 = 16;
+/// Extra points for a character matched right after the previous one.
+const CONSECUTIVE: i32 = 12;
+/// Extra points for a character matched at the start of a word.
+const WORD_START: i32 = 8;
+/// Points lost for each character skipped between two matched ones.
+const GAP: i32 = 1;
+
+/// Matches `pattern` against `text` as a subsequence, ignoring case and whitespace in `pattern`.
+/// Returns a score, higher for tighter matches and matches at word starts, and the positions of
+/// the matched characters in `text`.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+	let pattern: Vec<char> = pattern
+		.chars()
+		.filter(|c| !c.is_whitespace())
+		.flat_map(char::to_lowercase)
+		.collect();
+	let text: Vec<char> = text.chars().collect();
+	let Some(&first) = pattern.first() else {
+		return Some((0, Vec::new()));
+	};
+
+	// The best match is not always the leftmost one, so every start is tried.
+	(0..text.len())
+		.filter(|&start| lowercase_eq(text[start], first))
+		.filter_map(|start| match_from(&pattern, &text, start))
+		.max_by_key(|(score, positions)| (*score, std::cmp::Reverse(positions[0])))
+}
+
+fn match_from(pattern: &[char], text: &[char], start: usize) -> Option<(i32, Vec<usize>)> {
+	let mut positions = vec![start];
+	let mut score = MATCH + bonus(text, start);
+	for &c in &pattern[1..] {
+		let previous = positions[positions.len() - 1];
+		let position = (previous + 1..text.len()).find(|&i| lowercase_eq(text[i], c))?;
+		score += MATCH + bonus(text, position);
+		if position == previous + 1 {
+			score += CONSECUTIVE;
+		} else {
+			score -= GAP * (position - previous - 1) as i32;
+		}
+		positions.push(position);
+	}
+	Some((score, positions))
+}
+
+fn bonus(text: &[char], position: usize) -> i32 {
+	let word_start = match position.checked_sub(1).map(|i| text[i]) {
+		None => true,
+		Some(previous) => !previous.is_alphanumeric() || previous.is_lowercase() && text[position].is_uppercase(),
+	};
+	if word_start {
+		WORD_START
+	} else {
+		0
+	}
+}
+
+fn lowercase_eq(c: char, lowercase: char) -> bool {
+	c.to_lowercase().eq(std::iter::once(lowercase))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn positions(pattern: &str, text: &str) -> Option<Vec<usize>> {
+		fuzzy_match(pattern, text).map(|(_, positions)| positions)
+	}
+
+	fn score(pattern: &str, text: &str) -> i32 {
+		fuzzy_match(pattern, text).expect("the pattern matches").0
+	}
+
+	#[test]
+	fn finds_match_positions() {
+		assert_eq!(positions("gbd", "Gruvbox Dark"), Some(vec![0, 4, 8]));
+		assert_eq!(positions("G D", "gruvbox dark"), Some(vec![0, 8]));
+		// Positions count characters, not bytes.
+		assert_eq!(positions("én", "Café Noir"), Some(vec![3, 5]));
+		assert_eq!(positions("", "Dracula"), Some(Vec::new()));
+	}
+
+	#[test]
+	fn prefers_word_starts_to_scattered_matches() {
+		assert!(score("gd", "Gruvbox Dark") > score("gd", "Dogged"));
+		assert!(score("tn", "TokyoNight") > score("tn", "Tartan"));
+		// The best match is not the leftmost one.
+		assert_eq!(positions("ob", "Solarized Ocean Breeze"), Some(vec![10, 16]));
+	}
+
+	#[test]
+	fn prefers_consecutive_matches() {
+		assert!(score("dark", "Dark") > score("dark", "Dimmed Ark"));
+	}
+
+	#[test]
+	fn rejects_non_matches() {
+		assert_eq!(fuzzy_match("xyz", "Dracula"), None);
+		// The characters must appear in order.
+		assert_eq!(fuzzy_match("kd", "Dark"), None);
+		assert_eq!(fuzzy_match("darkk", "Dark"), None);
+	}
+}
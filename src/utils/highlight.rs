@@ -0,0 +1,632 @@
+//! A small syntax highlighter for previewing themes with source files. It knows the comments,
+//! strings and keywords of common languages, which is all a preview needs, and colors them with
+//! the ANSI colors terminal editors use by default.
+
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+
+/// Lines past these aren't shown in the preview anyway.
+const MAX_LINES: usize = 500;
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+	Plain,
+	Comment,
+	String,
+	Number,
+	Constant,
+	Keyword,
+	Type,
+	Function,
+	/// Attributes, decorators, macros and preprocessor lines.
+	Meta,
+}
+
+impl Token {
+	fn style(self) -> Style {
+		let style = Style::default();
+		match self {
+			Self::Plain => style,
+			Self::Comment => style.fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+			Self::String => style.fg(Color::Green),
+			Self::Number | Self::Constant => style.fg(Color::Red),
+			Self::Keyword => style.fg(Color::Magenta),
+			Self::Type => style.fg(Color::Yellow),
+			Self::Function => style.fg(Color::Blue),
+			Self::Meta => style.fg(Color::Cyan),
+		}
+	}
+}
+
+struct Language {
+	extensions: &'static [&'static str],
+	keywords: &'static [&'static str],
+	types: &'static [&'static str],
+	constants: &'static [&'static str],
+	line_comments: &'static [&'static str],
+	block_comment: Option<(&'static str, &'static str)>,
+	/// String delimiters; a tripled quote starts a string that may span lines.
+	quotes: &'static [char],
+	/// Whether names starting with a capital letter are types.
+	capitalized_types: bool,
+	/// What starts an attribute or decorator, which runs to the end of its name, or to the end of
+	/// the line for `#`.
+	meta: &'static [&'static str],
+}
+
+const LANGUAGES: [Language; 10] = [
+	Language {
+		extensions: &["rs"],
+		keywords: &[
+			"as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "fn",
+			"for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self",
+			"static", "struct", "super", "trait", "type", "unsafe", "use", "where", "while",
+		],
+		types: &[
+			"bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8", "u16", "u32", "u64",
+			"u128", "usize", "Self",
+		],
+		constants: &["true", "false", "None", "Some", "Ok", "Err"],
+		line_comments: &["//"],
+		block_comment: Some(("/*", "*/")),
+		quotes: &['"'],
+		capitalized_types: true,
+		meta: &["#"],
+	},
+	Language {
+		extensions: &["py", "pyi"],
+		keywords: &[
+			"and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else",
+			"except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal", "not",
+			"or", "pass", "raise", "return", "try", "while", "with", "yield", "match", "case",
+		],
+		types: &[
+			"bool", "bytes", "dict", "float", "int", "list", "object", "set", "str", "tuple",
+		],
+		constants: &["True", "False", "None", "self", "cls"],
+		line_comments: &["#"],
+		block_comment: None,
+		quotes: &['"', '\''],
+		capitalized_types: true,
+		meta: &["@"],
+	},
+	Language {
+		extensions: &["js", "jsx", "mjs", "cjs", "ts", "tsx"],
+		keywords: &[
+			"async",
+			"await",
+			"break",
+			"case",
+			"catch",
+			"class",
+			"const",
+			"continue",
+			"default",
+			"delete",
+			"do",
+			"else",
+			"export",
+			"extends",
+			"finally",
+			"for",
+			"from",
+			"function",
+			"if",
+			"import",
+			"in",
+			"instanceof",
+			"interface",
+			"let",
+			"new",
+			"of",
+			"return",
+			"switch",
+			"throw",
+			"try",
+			"type",
+			"typeof",
+			"var",
+			"while",
+			"yield",
+		],
+		types: &["any", "boolean", "number", "string", "unknown", "void", "never"],
+		constants: &["true", "false", "null", "undefined", "this", "NaN"],
+		line_comments: &["//"],
+		block_comment: Some(("/*", "*/")),
+		quotes: &['"', '\'', '`'],
+		capitalized_types: true,
+		meta: &["@"],
+	},
+	Language {
+		extensions: &["go"],
+		keywords: &[
+			"break",
+			"case",
+			"chan",
+			"const",
+			"continue",
+			"default",
+			"defer",
+			"else",
+			"fallthrough",
+			"for",
+			"func",
+			"go",
+			"goto",
+			"if",
+			"import",
+			"interface",
+			"map",
+			"package",
+			"range",
+			"return",
+			"select",
+			"struct",
+			"switch",
+			"type",
+			"var",
+		],
+		types: &[
+			"bool", "byte", "error", "float32", "float64", "int", "int8", "int16", "int32", "int64", "rune", "string",
+			"uint", "uint8", "uint16", "uint32", "uint64", "uintptr", "any",
+		],
+		constants: &["true", "false", "nil", "iota"],
+		line_comments: &["//"],
+		block_comment: Some(("/*", "*/")),
+		quotes: &['"', '`'],
+		capitalized_types: false,
+		meta: &[],
+	},
+	Language {
+		extensions: &["c", "h", "cc", "cpp", "cxx", "hpp", "hh"],
+		keywords: &[
+			"break",
+			"case",
+			"class",
+			"const",
+			"constexpr",
+			"continue",
+			"default",
+			"delete",
+			"do",
+			"else",
+			"enum",
+			"extern",
+			"for",
+			"goto",
+			"if",
+			"inline",
+			"namespace",
+			"new",
+			"private",
+			"protected",
+			"public",
+			"return",
+			"sizeof",
+			"static",
+			"struct",
+			"switch",
+			"template",
+			"typedef",
+			"typename",
+			"union",
+			"using",
+			"virtual",
+			"volatile",
+			"while",
+		],
+		types: &[
+			"auto", "bool", "char", "double", "float", "int", "long", "short", "signed", "size_t", "unsigned", "void",
+		],
+		constants: &["true", "false", "NULL", "nullptr", "this"],
+		line_comments: &["//"],
+		block_comment: Some(("/*", "*/")),
+		quotes: &['"', '\''],
+		capitalized_types: false,
+		meta: &["#"],
+	},
+	Language {
+		extensions: &["java", "kt", "cs"],
+		keywords: &[
+			"abstract",
+			"break",
+			"case",
+			"catch",
+			"class",
+			"continue",
+			"default",
+			"do",
+			"else",
+			"enum",
+			"extends",
+			"final",
+			"finally",
+			"for",
+			"fun",
+			"if",
+			"implements",
+			"import",
+			"interface",
+			"namespace",
+			"new",
+			"override",
+			"package",
+			"private",
+			"protected",
+			"public",
+			"return",
+			"static",
+			"switch",
+			"throw",
+			"throws",
+			"try",
+			"using",
+			"val",
+			"var",
+			"void",
+			"when",
+			"while",
+		],
+		types: &[
+			"boolean", "byte", "char", "double", "float", "int", "long", "short", "string",
+		],
+		constants: &["true", "false", "null", "this", "super"],
+		line_comments: &["//"],
+		block_comment: Some(("/*", "*/")),
+		quotes: &['"', '\''],
+		capitalized_types: true,
+		meta: &["@"],
+	},
+	Language {
+		extensions: &["sh", "bash", "zsh"],
+		keywords: &[
+			"case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in", "local",
+			"return", "select", "then", "until", "while",
+		],
+		types: &[],
+		constants: &["true", "false"],
+		line_comments: &["#"],
+		block_comment: None,
+		quotes: &['"', '\''],
+		capitalized_types: false,
+		meta: &["$"],
+	},
+	Language {
+		extensions: &["rb"],
+		keywords: &[
+			"begin", "break", "case", "class", "def", "do", "else", "elsif", "end", "ensure", "if", "in", "module",
+			"next", "rescue", "return", "then", "unless", "until", "when", "while", "yield", "require",
+		],
+		types: &[],
+		constants: &["true", "false", "nil", "self"],
+		line_comments: &["#"],
+		block_comment: None,
+		quotes: &['"', '\''],
+		capitalized_types: true,
+		meta: &["@"],
+	},
+	Language {
+		extensions: &["lua"],
+		keywords: &[
+			"and", "break", "do", "else", "elseif", "end", "for", "function", "goto", "if", "in", "local", "not", "or",
+			"repeat", "return", "then", "until", "while",
+		],
+		types: &[],
+		constants: &["true", "false", "nil", "self"],
+		line_comments: &["--"],
+		block_comment: Some(("--[[", "]]")),
+		quotes: &['"', '\''],
+		capitalized_types: false,
+		meta: &[],
+	},
+	Language {
+		extensions: &["toml", "yaml", "yml", "ini", "conf"],
+		keywords: &[],
+		types: &[],
+		constants: &["true", "false", "null"],
+		line_comments: &["#", ";"],
+		block_comment: None,
+		quotes: &['"', '\''],
+		capitalized_types: false,
+		meta: &["["],
+	},
+];
+
+/// For files in other languages: just strings and numbers.
+const PLAIN: Language = Language {
+	extensions: &[],
+	keywords: &[],
+	types: &[],
+	constants: &[],
+	line_comments: &[],
+	block_comment: None,
+	quotes: &[],
+	capitalized_types: false,
+	meta: &[],
+};
+
+/// `source` highlighted for the language its file `extension` stands for, with named colors.
+pub fn highlight_source(source: &str, extension: &str) -> Vec<Spans<'static>> {
+	let language = LANGUAGES
+		.iter()
+		.find(|language| language.extensions.contains(&extension.to_lowercase().as_str()))
+		.unwrap_or(&PLAIN);
+
+	// What a line leaves open for the next one: the end of a comment or string.
+	let mut open: Option<(Token, String)> = None;
+	source
+		.lines()
+		.take(MAX_LINES)
+		.map(|line| {
+			let line = expand_tabs(line);
+			let mut spans = Spans::default();
+			let mut rest = line.as_str();
+
+			if let Some((token, end)) = open.take() {
+				match rest.find(&end) {
+					Some(index) => {
+						push(&mut spans, token, &rest[..index + end.len()]);
+						rest = &rest[index + end.len()..];
+					},
+					None => {
+						push(&mut spans, token, rest);
+						open = Some((token, end));
+						return spans;
+					},
+				}
+			}
+			while !rest.is_empty() {
+				let (token, length, unclosed) = next_token(language, rest, &spans);
+				push(&mut spans, token, &rest[..length]);
+				open = unclosed.map(|end| (token, end));
+				rest = &rest[length..];
+			}
+			spans
+		})
+		.collect()
+}
+
+/// The token `text` starts with and its length in bytes, with the delimiter it leaves to be closed
+/// on a later line. `before` is what comes before `text` on its line.
+fn next_token(language: &Language, text: &str, before: &Spans) -> (Token, usize, Option<String>) {
+	let after_word = before.0.last().is_some_and(|span| span.content.ends_with(is_word_char));
+	let line_start = before.0.iter().all(|span| span.content.trim().is_empty());
+
+	if let Some((start, end)) = language.block_comment.filter(|(start, _)| text.starts_with(start)) {
+		return match text[start.len()..].find(end) {
+			Some(index) => (Token::Comment, start.len() + index + end.len(), None),
+			None => (Token::Comment, text.len(), Some(end.to_string())),
+		};
+	}
+	if language.line_comments.iter().any(|comment| text.starts_with(comment)) {
+		return (Token::Comment, text.len(), None);
+	}
+	if let Some(quote) = language.quotes.iter().copied().find(|quote| text.starts_with(*quote)) {
+		return string(text, quote);
+	}
+	// Rust chars, leaving lifetimes such as `'a` alone.
+	if language.extensions.contains(&"rs") && text.starts_with('\'') {
+		let quoted = if text[1..].starts_with('\\') {
+			text.get(3..).and_then(|rest| rest.find('\'')).map(|index| index + 4)
+		} else {
+			text[1..]
+				.char_indices()
+				.nth(1)
+				.filter(|(_, c)| *c == '\'')
+				.map(|(index, _)| index + 2)
+		};
+		if let Some(length) = quoted {
+			return (Token::String, length, None);
+		}
+	}
+	if let Some(meta) = language.meta.iter().find(|meta| text.starts_with(**meta)) {
+		let length = match *meta {
+			"#" | "[" if line_start => text.len(),
+			"#" | "[" => 0,
+			_ => {
+				meta.len()
+					+ text[meta.len()..]
+						.find(|c| !is_word_char(c))
+						.unwrap_or(text.len() - meta.len())
+			},
+		};
+		if length > meta.len() && !after_word {
+			return (Token::Meta, length, None);
+		}
+	}
+
+	let word = text.find(|c| !is_word_char(c)).unwrap_or(text.len());
+	if word == 0 || after_word {
+		let length = if word == 0 {
+			text.chars().next().map_or(1, char::len_utf8)
+		} else {
+			word
+		};
+		return (Token::Plain, length, None);
+	}
+	let name = &text[..word];
+	let next = text[word..].trim_start().chars().next();
+	let token = if name.starts_with(|c: char| c.is_ascii_digit()) {
+		Token::Number
+	} else if language.keywords.contains(&name) {
+		Token::Keyword
+	} else if language.constants.contains(&name)
+		|| name.len() > 1 && name.chars().all(|c| c.is_uppercase() || c.is_ascii_digit() || c == '_')
+	{
+		Token::Constant
+	} else if language.types.contains(&name)
+		|| language.capitalized_types && name.starts_with(|c: char| c.is_uppercase())
+	{
+		Token::Type
+	} else if next == Some('!') && language.extensions.contains(&"rs") {
+		Token::Meta
+	} else if next == Some('(') {
+		Token::Function
+	} else {
+		Token::Plain
+	};
+	(token, word, None)
+}
+
+/// A string starting with `quote`, which may continue on the next lines if the quote is tripled.
+fn string(text: &str, quote: char) -> (Token, usize, Option<String>) {
+	let triple = quote.to_string().repeat(3);
+	if text.starts_with(&triple) {
+		return match text[3..].find(&triple) {
+			Some(index) => (Token::String, index + 6, None),
+			None => (Token::String, text.len(), Some(triple)),
+		};
+	}
+
+	let mut escaped = false;
+	for (index, c) in text.char_indices().skip(1) {
+		match c {
+			_ if escaped => escaped = false,
+			'\\' => escaped = true,
+			c if c == quote => return (Token::String, index + c.len_utf8(), None),
+			_ => {},
+		}
+	}
+	(Token::String, text.len(), None)
+}
+
+fn is_word_char(c: char) -> bool {
+	c.is_alphanumeric() || c == '_'
+}
+
+/// Adds `text` to the line, merged into the last span if that is styled the same.
+fn push(spans: &mut Spans<'static>, token: Token, text: &str) {
+	let style = token.style();
+	match spans.0.last_mut() {
+		Some(last) if last.style == style => last.content.to_mut().push_str(text),
+		_ => spans.0.push(Span::styled(text.to_string(), style)),
+	}
+}
+
+fn expand_tabs(line: &str) -> String {
+	let mut expanded = String::new();
+	for c in line.chars() {
+		if c == '\t' {
+			let spaces = TAB_WIDTH - expanded.chars().count() % TAB_WIDTH;
+			expanded.push_str(&" ".repeat(spaces));
+		} else {
+			expanded.push(c);
+		}
+	}
+	expanded
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn highlighted(source: &str, extension: &str) -> Vec<Vec<(String, Style)>> {
+		highlight_source(source, extension)
+			.into_iter()
+			.map(|line| {
+				line.0
+					.into_iter()
+					.map(|span| (span.content.into_owned(), span.style))
+					.collect()
+			})
+			.collect()
+	}
+
+	fn line(tokens: &[(Token, &str)]) -> Vec<(String, Style)> {
+		tokens
+			.iter()
+			.map(|(token, text)| (text.to_string(), token.style()))
+			.collect()
+	}
+
+	#[test]
+	fn strings_keep_escaped_quotes() {
+		assert_eq!(
+			highlighted(r#"let s = "a \"b\" \\";"#, "rs"),
+			[line(&[
+				(Token::Keyword, "let"),
+				(Token::Plain, " s = "),
+				(Token::String, r#""a \"b\" \\""#),
+				(Token::Plain, ";"),
+			])]
+		);
+		assert_eq!(
+			highlighted(r"print('it\'s')", "py"),
+			[line(&[
+				(Token::Function, "print"),
+				(Token::Plain, "("),
+				(Token::String, r"'it\'s'"),
+				(Token::Plain, ")"),
+			])]
+		);
+	}
+
+	#[test]
+	fn comments_and_strings_span_lines() {
+		assert_eq!(
+			highlighted("a /* one\ntwo\nthree */ b // four", "c"),
+			[
+				line(&[(Token::Plain, "a "), (Token::Comment, "/* one")]),
+				line(&[(Token::Comment, "two")]),
+				line(&[
+					(Token::Comment, "three */"),
+					(Token::Plain, " b "),
+					(Token::Comment, "// four")
+				]),
+			]
+		);
+		assert_eq!(
+			highlighted("x = \"\"\"doc\nmore\"\"\" + 1", "py"),
+			[
+				line(&[(Token::Plain, "x = "), (Token::String, "\"\"\"doc")]),
+				line(&[
+					(Token::String, "more\"\"\""),
+					(Token::Plain, " + "),
+					(Token::Number, "1")
+				]),
+			]
+		);
+	}
+
+	#[test]
+	fn numbers_start_with_a_digit() {
+		assert_eq!(
+			highlighted("let x1 = 0xff + 42;", "rs"),
+			[line(&[
+				(Token::Keyword, "let"),
+				(Token::Plain, " x1 = "),
+				(Token::Number, "0xff"),
+				(Token::Plain, " + "),
+				(Token::Number, "42"),
+				(Token::Plain, ";"),
+			])]
+		);
+	}
+
+	#[test]
+	fn keywords_are_whole_words() {
+		assert_eq!(
+			highlighted("fn main() -> Option<u8> { formats }", "RS"),
+			[line(&[
+				(Token::Keyword, "fn"),
+				(Token::Plain, " "),
+				(Token::Function, "main"),
+				(Token::Plain, "() -> "),
+				(Token::Type, "Option"),
+				(Token::Plain, "<"),
+				(Token::Type, "u8"),
+				(Token::Plain, "> { formats }"),
+			])]
+		);
+	}
+
+	#[test]
+	fn unknown_extensions_are_plain_text() {
+		assert_eq!(
+			highlighted("let s = \"fn\"; // 7", "xyz"),
+			[line(&[(Token::Plain, "let s = \"fn\"; // "), (Token::Number, "7")])]
+		);
+		assert_eq!(highlighted("let x", ""), [line(&[(Token::Plain, "let x")])]);
+	}
+}
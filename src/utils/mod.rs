@@ -1 +1,8 @@
+pub mod ansi;
+pub mod color;
 pub mod example;
+pub mod fuzzy;
+pub mod highlight;
+pub mod names;
+pub mod scenes;
+pub mod swatches;
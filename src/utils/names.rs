@@ -0,0 +1,126 @@
+use std::cmp::Ordering;
+
+const VARIANT_SUFFIXES: [&str; 2] = [".dark", ".light"];
+
+/// Turns a theme name into a title for display: `Embers.dark` becomes `Embers (dark)`,
+/// `Gruvbox-Dark` becomes `Gruvbox Dark` and `paper-theme` becomes `Paper theme`. A spaced
+/// ` - ` is kept as is, since it usually separates an author from the theme name.
+pub fn display_name(name: &str) -> String {
+	let (base, variant) = VARIANT_SUFFIXES
+		.iter()
+		.find_map(|suffix| {
+			let base = name.strip_suffix(suffix)?;
+			Some((base, Some(&suffix[1..])))
+		})
+		.unwrap_or((name, None));
+
+	let mut title = String::with_capacity(name.len() + 3);
+	let chars: Vec<char> = base.chars().collect();
+	for (i, &c) in chars.iter().enumerate() {
+		let spaced = i > 0 && chars[i - 1] == ' ' || chars.get(i + 1) == Some(&' ');
+		if (c == '_' || c == '-') && !spaced {
+			title.push(' ');
+		} else {
+			title.push(c);
+		}
+	}
+
+	let first_word = title.split(' ').next().unwrap_or_default();
+	if first_word.chars().all(|c| !c.is_uppercase()) {
+		let mut chars = title.chars();
+		if let Some(first) = chars.next() {
+			title = first.to_uppercase().chain(chars).collect();
+		}
+	}
+
+	match variant {
+		Some(variant) => format!("{title} ({variant})"),
+		None => title,
+	}
+}
+
+/// Case-insensitive natural ordering, so that `Theme2` sorts before `Theme10`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+	let mut a = a.chars().peekable();
+	let mut b = b.chars().peekable();
+
+	loop {
+		match (a.peek().copied(), b.peek().copied()) {
+			(None, None) => return Ordering::Equal,
+			(None, Some(_)) => return Ordering::Less,
+			(Some(_), None) => return Ordering::Greater,
+			(Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+				let x = take_number(&mut a);
+				let y = take_number(&mut b);
+				let ordering = x
+					.trim_start_matches('0')
+					.len()
+					.cmp(&y.trim_start_matches('0').len())
+					.then_with(|| x.trim_start_matches('0').cmp(y.trim_start_matches('0')));
+				if ordering != Ordering::Equal {
+					return ordering;
+				}
+			},
+			(Some(x), Some(y)) => {
+				let ordering = x.to_lowercase().cmp(y.to_lowercase());
+				if ordering != Ordering::Equal {
+					return ordering;
+				}
+				a.next();
+				b.next();
+			},
+		}
+	}
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+	let mut number = String::new();
+	while let Some(c) = chars.next_if(char::is_ascii_digit) {
+		number.push(c);
+	}
+	number
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn orders_numbers_by_value() {
+		assert_eq!(natural_cmp("Theme 2", "Theme 10"), Ordering::Less);
+		assert_eq!(natural_cmp("Theme10", "Theme9"), Ordering::Greater);
+		assert_eq!(natural_cmp("Theme 02", "Theme 2"), Ordering::Equal);
+		assert_eq!(natural_cmp("Theme", "Theme 1"), Ordering::Less);
+
+		let mut names = vec!["Theme 10", "theme 2", "Theme 1", "Alpha", "Theme 1b"];
+		names.sort_by(|a, b| natural_cmp(a, b));
+		assert_eq!(names, ["Alpha", "Theme 1", "Theme 1b", "theme 2", "Theme 10"]);
+	}
+
+	#[test]
+	fn ignores_case() {
+		assert_eq!(natural_cmp("gruvbox", "Gruvbox"), Ordering::Equal);
+		assert_eq!(natural_cmp("ayu", "Base16"), Ordering::Less);
+		assert_eq!(natural_cmp("Zenburn", "alabaster"), Ordering::Greater);
+	}
+
+	#[test]
+	fn compares_separators_as_characters() {
+		assert_eq!(natural_cmp("one-dark", "one_dark"), Ordering::Less);
+		assert_eq!(natural_cmp("one dark", "one-dark"), Ordering::Less);
+		assert_eq!(natural_cmp("one_dark 2", "one_dark 10"), Ordering::Less);
+	}
+
+	#[test]
+	fn prettifies_names() {
+		assert_eq!(display_name("Gruvbox-Dark"), "Gruvbox Dark");
+		assert_eq!(display_name("paper-theme"), "Paper theme");
+		assert_eq!(display_name("github_dark_default"), "Github dark default");
+		assert_eq!(display_name("Embers.dark"), "Embers (dark)");
+		assert_eq!(display_name("tokyo-night.light"), "Tokyo night (light)");
+		// A capital anywhere in the first word means the author chose the case.
+		assert_eq!(display_name("iTerm2_Solarized"), "iTerm2 Solarized");
+		assert_eq!(display_name("Author - Theme"), "Author - Theme");
+		assert_eq!(display_name("Theme 10"), "Theme 10");
+	}
+}
@@ -0,0 +1,562 @@
+//! The scenes the preview pane cycles through. They are written in named colors, which
+//! `return_example` turns into the theme's, while the selection, search matches and the like are
+//! drawn in the theme's colors for them right away.
+
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+
+use crate::structured_data::structs::Colors;
+use crate::utils::ansi::parse_ansi;
+use crate::utils::color::Highlight;
+use crate::utils::example::{example_lines, rgb};
+use crate::utils::highlight::highlight_source;
+
+#[derive(Debug, Clone)]
+pub enum Scene {
+	Rust,
+	Shell,
+	GitDiff,
+	Htop,
+	Compiler,
+	ManPage,
+	ViMode,
+	/// Lines read at the start, such as captured terminal output, with a name to show for them.
+	Loaded(String, Vec<Spans<'static>>),
+}
+
+impl Scene {
+	/// The scenes to cycle through, starting with `sample` if there is one. A `source` file takes
+	/// the place of the Rust snippet.
+	pub fn all(sample: Option<Self>, source: Option<Self>) -> Vec<Self> {
+		sample
+			.into_iter()
+			.chain([
+				source.unwrap_or(Self::Rust),
+				Self::Shell,
+				Self::GitDiff,
+				Self::Htop,
+				Self::Compiler,
+				Self::ManPage,
+				Self::ViMode,
+			])
+			.collect()
+	}
+
+	/// Terminal output with its escape sequences, as `script` or `tmux capture-pane -e` record it.
+	pub fn capture(name: impl Into<String>, content: &str) -> Self {
+		Self::Loaded(name.into(), parse_ansi(content).lines)
+	}
+
+	/// A source file, highlighted for the language its `extension` stands for.
+	pub fn source(name: impl Into<String>, content: &str, extension: &str) -> Self {
+		Self::Loaded(name.into(), highlight_source(content, extension))
+	}
+
+	pub fn name(&self) -> &str {
+		match self {
+			Self::Rust => "Rust",
+			Self::Shell => "ls",
+			Self::GitDiff => "git diff",
+			Self::Htop => "htop",
+			Self::Compiler => "cargo build",
+			Self::ManPage => "man page",
+			Self::ViMode => "vi mode",
+			Self::Loaded(name, _) => name,
+		}
+	}
+
+	pub fn lines(&self, colors: &Colors) -> Vec<Spans<'static>> {
+		match self {
+			Self::Rust => {
+				let mut lines = example_lines();
+				if let Some(last) = lines.last_mut() {
+					last.0.push(highlight(colors, Highlight::Cursor, " "));
+				}
+				lines
+			},
+			Self::Shell => shell(colors),
+			Self::GitDiff => git_diff(colors),
+			Self::Htop => htop(),
+			Self::Compiler => compiler(),
+			Self::ManPage => man_page(),
+			Self::ViMode => vi_mode(colors),
+			Self::Loaded(_, lines) => lines.clone(),
+		}
+	}
+}
+
+fn fg(color: Color) -> Style {
+	Style::default().fg(color)
+}
+
+fn bold(color: Color) -> Style {
+	fg(color).add_modifier(Modifier::BOLD)
+}
+
+fn on(foreground: Color, background: Color) -> Style {
+	fg(foreground).bg(background)
+}
+
+fn dim() -> Style {
+	Style::default().add_modifier(Modifier::DIM)
+}
+
+/// `text` in the colors the theme gives `highlight`.
+fn highlight(colors: &Colors, highlight: Highlight, text: &'static str) -> Span<'static> {
+	let (foreground, background) = highlight.colors(colors);
+	let mut style = Style::default();
+	if let Some(foreground) = foreground {
+		style = style.fg(rgb(foreground));
+	}
+	if let Some(background) = background {
+		style = style.bg(rgb(background));
+	}
+	Span::styled(text, style)
+}
+
+fn prompt(command: &'static str) -> Spans<'static> {
+	Spans::from(vec![
+		Span::styled("user@host", bold(Color::LightGreen)),
+		Span::raw(":"),
+		Span::styled("~/themes", bold(Color::LightBlue)),
+		Span::raw(" "),
+		Span::styled("(main)", fg(Color::LightMagenta)),
+		Span::raw(" $ "),
+		Span::raw(command),
+	])
+}
+
+/// A long listing, with `LS_COLORS` defaults for each kind of file.
+fn shell(colors: &Colors) -> Vec<Spans<'static>> {
+	let entry = |details: &'static str, name: Span<'static>, rest: &'static str| {
+		Spans::from(vec![Span::raw(details), name, Span::raw(rest)])
+	};
+	let mut last = prompt("");
+	last.0.push(highlight(colors, Highlight::Cursor, " "));
+
+	vec![
+		prompt("ls --color -l"),
+		Spans::from(Span::raw("total 4120")),
+		entry(
+			"drwxr-xr-x  4 user staff  128 Oct 18 09:12 ",
+			Span::styled("assets", bold(Color::Blue)),
+			"",
+		),
+		entry(
+			"-rwxr-xr-x  1 user staff 8.2K Oct 18 09:12 ",
+			Span::styled("build.sh", bold(Color::Green)),
+			"",
+		),
+		entry(
+			"-rw-r--r--  1 user staff  17K Oct 17 21:40 ",
+			Span::raw("Cargo.lock"),
+			"",
+		),
+		entry(
+			"lrwxr-xr-x  1 user staff   11 Oct 16 11:02 ",
+			Span::styled("config", bold(Color::Cyan)),
+			" -> ../config",
+		),
+		entry(
+			"prw-r--r--  1 user staff    0 Oct 18 09:30 ",
+			Span::styled("events", on(Color::Yellow, Color::Black)),
+			"",
+		),
+		entry(
+			"-rw-r--r--  1 user staff 3.1M Oct 15 08:45 ",
+			Span::styled("logo.png", bold(Color::Magenta)),
+			"",
+		),
+		entry(
+			"-rw-r--r--  1 user staff 912K Oct 14 19:03 ",
+			Span::styled("release.tar.gz", bold(Color::Red)),
+			"",
+		),
+		entry(
+			"-rwsr-xr-x  1 root wheel  52K Oct 12 10:00 ",
+			Span::styled("setup-helper", on(Color::Gray, Color::Red)),
+			"",
+		),
+		entry(
+			"drwxrwxrwt  9 root wheel  288 Oct 18 09:31 ",
+			Span::styled("tmp", on(Color::Black, Color::Green)),
+			"",
+		),
+		prompt("cat missing.txt"),
+		Spans::from(Span::styled(
+			"cat: missing.txt: No such file or directory",
+			fg(Color::LightRed),
+		)),
+		Spans::from(vec![
+			Span::styled("[1] ", bold(Color::LightYellow)),
+			Span::styled("exit status 1", fg(Color::LightCyan)),
+		]),
+		last,
+	]
+}
+
+fn git_diff(colors: &Colors) -> Vec<Spans<'static>> {
+	let removed = |text: &'static str| Spans::from(Span::styled(text, fg(Color::Red)));
+	let added = |text: &'static str| Spans::from(Span::styled(text, fg(Color::Green)));
+	let commit = |hash: &'static str, message: &'static str| {
+		Spans::from(vec![Span::styled(hash, fg(Color::Yellow)), Span::raw(message)])
+	};
+	let mut last = prompt("");
+	last.0.push(highlight(colors, Highlight::Cursor, " "));
+
+	vec![
+		prompt("git log --oneline -3"),
+		Spans::from(vec![
+			Span::styled("a6e418d ", fg(Color::Yellow)),
+			Span::styled("(", fg(Color::Yellow)),
+			Span::styled("HEAD -> ", bold(Color::LightCyan)),
+			Span::styled("main", bold(Color::LightGreen)),
+			Span::styled(", ", fg(Color::Yellow)),
+			Span::styled("origin/main", bold(Color::LightRed)),
+			Span::styled(")", fg(Color::Yellow)),
+			Span::raw(" Make picker keys configurable"),
+		]),
+		commit("9ecab8e ", "Add incremental fuzzy search"),
+		commit("8478426 ", "Mark broken themes"),
+		prompt("git diff"),
+		Spans::from(Span::styled(
+			"diff --git a/src/theme.rs b/src/theme.rs",
+			bold(Color::White),
+		)),
+		Spans::from(Span::styled("index 3f2a1c9..8b7d4e0 100644", bold(Color::White))),
+		Spans::from(Span::styled("--- a/src/theme.rs", bold(Color::White))),
+		Spans::from(Span::styled("+++ b/src/theme.rs", bold(Color::White))),
+		Spans::from(vec![
+			Span::styled("@@ -12,7 +12,8 @@", fg(Color::Cyan)),
+			Span::raw(" impl Theme {"),
+		]),
+		Spans::from(Span::raw("     pub fn load(path: &Path) -> Result<Self> {")),
+		removed("-        let text = fs::read_to_string(path)?;"),
+		removed("-        toml::from_str(&text)"),
+		added("+        let text = fs::read_to_string(path)"),
+		added("+            .map_err(|err| Error::Read(path.into(), err))?;"),
+		Spans::from(vec![
+			Span::styled("+        toml::from_str(&text).map_err(Error::Parse)", fg(Color::Green)),
+			Span::styled("  ", on(Color::Red, Color::Red)),
+		]),
+		Spans::from(Span::raw("     }")),
+		last,
+	]
+}
+
+/// Meters and a process list, in htop's default colors.
+fn htop() -> Vec<Spans<'static>> {
+	let cpu =
+		|number: &'static str, user: &'static str, kernel: &'static str, low: &'static str, rest: &'static str| {
+			vec![
+				Span::styled(number, fg(Color::Cyan)),
+				Span::styled("[", bold(Color::White)),
+				Span::styled(user, fg(Color::Green)),
+				Span::styled(kernel, fg(Color::Red)),
+				Span::styled(low, fg(Color::Blue)),
+				Span::styled(rest, dim()),
+				Span::styled("]", bold(Color::White)),
+			]
+		};
+	let process = |pid: &'static str, user: &'static str, res: &'static str, command: Vec<Span<'static>>| {
+		let mut spans = vec![
+			Span::raw(pid),
+			Span::raw(user),
+			Span::styled("20   0 ", dim()),
+			Span::styled(res, fg(Color::Cyan)),
+		];
+		spans.extend(command);
+		Spans::from(spans)
+	};
+	let keys = [
+		("F1", "Help  "),
+		("F2", "Setup "),
+		("F3", "Search"),
+		("F4", "Filter"),
+		("F5", "Tree  "),
+		("F10", "Quit  "),
+	]
+	.into_iter()
+	.flat_map(|(key, label)| [Span::raw(key), Span::styled(label, on(Color::Black, Color::Cyan))])
+	.collect::<Vec<_>>();
+
+	let mut first = cpu("    1", "|||||||||", "||||", "||", "     41.2%");
+	first.extend([
+		Span::styled("  Tasks: ", fg(Color::Cyan)),
+		Span::styled("142", bold(Color::White)),
+		Span::styled(", ", fg(Color::Cyan)),
+		Span::styled("611", bold(Color::LightGreen)),
+		Span::styled(" thr; ", fg(Color::Cyan)),
+		Span::styled("3", bold(Color::Green)),
+		Span::styled(" running", fg(Color::Cyan)),
+	]);
+	let mut second = cpu("    2", "|||||", "|||", "", "            23.8%");
+	second.extend([
+		Span::styled("  Load average: ", fg(Color::Cyan)),
+		Span::styled("1.42 ", bold(Color::White)),
+		Span::styled("0.98 ", bold(Color::Cyan)),
+		Span::styled("0.77", fg(Color::Cyan)),
+	]);
+
+	vec![
+		Spans::from(first),
+		Spans::from(second),
+		Spans::from(vec![
+			Span::styled("  Mem", fg(Color::Cyan)),
+			Span::styled("[", bold(Color::White)),
+			Span::styled("||||||||", fg(Color::Green)),
+			Span::styled("|||", fg(Color::Blue)),
+			Span::styled("|||", fg(Color::Yellow)),
+			Span::styled(" 5.1G/16.0G", dim()),
+			Span::styled("]", bold(Color::White)),
+			Span::styled("  Uptime: ", fg(Color::Cyan)),
+			Span::styled("3 days, 04:12:55", bold(Color::White)),
+		]),
+		Spans::from(vec![
+			Span::styled("  Swp", fg(Color::Cyan)),
+			Span::styled("[", bold(Color::White)),
+			Span::styled("|", fg(Color::Red)),
+			Span::styled("               128M/4.0G", dim()),
+			Span::styled("]", bold(Color::White)),
+		]),
+		Spans::from(Span::raw("")),
+		Spans::from(Span::styled(
+			"    PID USER     PRI  NI  RES  Command                          ",
+			on(Color::Black, Color::Green),
+		)),
+		Spans::from(Span::styled(
+			"   2817 user      20   0 412M  alacritty                        ",
+			on(Color::Black, Color::Cyan),
+		)),
+		process(
+			"   3120 ",
+			"user      ",
+			" 96M  ",
+			vec![Span::styled("cargo", bold(Color::White)), Span::raw(" build --release")],
+		),
+		// Threads are green, and dim ones are drawn in the theme's dim colors.
+		process(
+			"   3188 ",
+			"user      ",
+			" 88M  ",
+			vec![Span::styled(
+				"rustc --crate-name themes",
+				fg(Color::Green).add_modifier(Modifier::DIM),
+			)],
+		),
+		process(
+			"    845 ",
+			"root      ",
+			" 12M  ",
+			vec![
+				Span::styled("/usr/lib/", dim()),
+				Span::styled("Xorg", bold(Color::White)),
+			],
+		),
+		process(
+			"   1907 ",
+			"user      ",
+			"8.2M  ",
+			vec![Span::styled("tmux", bold(Color::White))],
+		),
+		process(
+			"     12 ",
+			"root      ",
+			"   0  ",
+			vec![Span::styled("[kworker/0:1-events]", fg(Color::DarkGray))],
+		),
+		Spans::from(keys),
+	]
+}
+
+/// Cargo output with a warning and an error, in rustc's colors.
+fn compiler() -> Vec<Spans<'static>> {
+	let gutter = |line: &'static str| Span::styled(line, bold(Color::LightBlue));
+
+	vec![
+		prompt("cargo build"),
+		Spans::from(vec![
+			Span::styled("   Compiling", bold(Color::Green)),
+			Span::raw(" themes v0.1.0 (/home/user/themes)"),
+		]),
+		Spans::from(vec![
+			Span::styled("warning", bold(Color::LightYellow)),
+			Span::styled(": unused variable: `index`", bold(Color::White)),
+		]),
+		Spans::from(vec![gutter("  --> "), Span::raw("src/picker.rs:42:9")]),
+		Spans::from(gutter("   |")),
+		Spans::from(vec![gutter("42 |"), Span::raw("     let index = 3;")]),
+		Spans::from(vec![
+			gutter("   |"),
+			Span::raw("         "),
+			Span::styled(
+				"^^^^^ help: prefix it with an underscore: `_index`",
+				bold(Color::LightYellow),
+			),
+		]),
+		Spans::from(vec![
+			gutter("   = "),
+			Span::styled("note", bold(Color::White)),
+			Span::raw(": `#[warn(unused_variables)]` on by default"),
+		]),
+		Spans::from(Span::raw("")),
+		Spans::from(vec![
+			Span::styled("error[E0308]", bold(Color::LightRed)),
+			Span::styled(": mismatched types", bold(Color::White)),
+		]),
+		Spans::from(vec![gutter("  --> "), Span::raw("src/picker.rs:57:22")]),
+		Spans::from(gutter("   |")),
+		Spans::from(vec![gutter("57 |"), Span::raw("     let count: u32 = names.len();")]),
+		Spans::from(vec![
+			gutter("   |"),
+			Span::raw("                "),
+			Span::styled("---", bold(Color::LightBlue)),
+			Span::raw("   "),
+			Span::styled("^^^^^^^^^^^ expected `u32`, found `usize`", bold(Color::LightRed)),
+		]),
+		Spans::from(vec![
+			gutter("   |"),
+			Span::raw("                "),
+			Span::styled("expected due to this", bold(Color::LightBlue)),
+		]),
+		Spans::from(Span::raw("")),
+		Spans::from(vec![
+			Span::styled("error", bold(Color::LightRed)),
+			Span::styled(
+				": could not compile `themes` due to 1 previous error",
+				bold(Color::White),
+			),
+		]),
+	]
+}
+
+/// The top of a man page as `less` shows it, with bold and underlined text.
+fn man_page() -> Vec<Spans<'static>> {
+	let heading = |text: &'static str| Spans::from(Span::styled(text, Style::default().add_modifier(Modifier::BOLD)));
+	let option = |text: &'static str| Span::styled(text, Style::default().add_modifier(Modifier::BOLD));
+	let argument = |text: &'static str| Span::styled(text, Style::default().add_modifier(Modifier::UNDERLINED));
+
+	vec![
+		Spans::from(Span::raw(
+			"LS(1)                    User Commands                    LS(1)",
+		)),
+		Spans::from(Span::raw("")),
+		heading("NAME"),
+		Spans::from(Span::raw("       ls - list directory contents")),
+		Spans::from(Span::raw("")),
+		heading("SYNOPSIS"),
+		Spans::from(vec![
+			Span::raw("       "),
+			option("ls"),
+			Span::raw(" ["),
+			argument("OPTION"),
+			Span::raw("]... ["),
+			argument("FILE"),
+			Span::raw("]..."),
+		]),
+		Spans::from(Span::raw("")),
+		heading("DESCRIPTION"),
+		Spans::from(vec![
+			Span::raw("       List information about the "),
+			argument("FILE"),
+			Span::raw("s (the current directory"),
+		]),
+		Spans::from(vec![Span::raw(
+			"       by default). Sort entries alphabetically if none",
+		)]),
+		Spans::from(vec![
+			Span::raw("       of "),
+			option("-cftuvSUX"),
+			Span::raw(" nor "),
+			option("--sort"),
+			Span::raw(" is specified."),
+		]),
+		Spans::from(Span::raw("")),
+		Spans::from(vec![
+			Span::raw("       "),
+			option("-a"),
+			Span::raw(", "),
+			option("--all"),
+		]),
+		Spans::from(Span::raw("              do not ignore entries starting with .")),
+		Spans::from(Span::raw("")),
+		Spans::from(vec![
+			Span::raw("       "),
+			option("--color"),
+			Span::raw("[="),
+			argument("WHEN"),
+			Span::raw("]"),
+		]),
+		Spans::from(vec![
+			Span::raw("              colorize the output; "),
+			argument("WHEN"),
+			Span::raw(" can be 'always', 'auto', or"),
+		]),
+		Spans::from(Span::styled(
+			" Manual page ls(1) line 1 (press h for help or q to quit)",
+			Style::default().add_modifier(Modifier::REVERSED),
+		)),
+	]
+}
+
+/// Scrollback in Alacritty's vi mode, with a selection, search matches, a URL hint and the vi mode
+/// cursor.
+fn vi_mode(colors: &Colors) -> Vec<Spans<'static>> {
+	let time = |time: &'static str| Span::styled(time, fg(Color::DarkGray));
+	let unit = || Span::styled(" backup[812]: ", fg(Color::Cyan));
+	let search = |highlight_kind| highlight(colors, highlight_kind, "error");
+
+	vec![
+		prompt("journalctl -u backup --since today"),
+		Spans::from(vec![time("02:00:01"), unit(), Span::raw("starting nightly backup")]),
+		Spans::from(vec![
+			time("02:00:03"),
+			unit(),
+			Span::raw("uploading to "),
+			highlight(colors, Highlight::HintStart, "j"),
+			highlight(colors, Highlight::HintEnd, "k"),
+			Span::styled(
+				"tps://example.com/nightly",
+				fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+			),
+		]),
+		Spans::from(vec![
+			time("02:04:27"),
+			unit(),
+			Span::raw("uploaded 1204 of 3310 chunks"),
+		]),
+		Spans::from(vec![
+			time("02:14:55"),
+			unit(),
+			search(Highlight::Match),
+			Span::styled(": ", bold(Color::Red)),
+			highlight(colors, Highlight::Selection, "connection reset by peer"),
+		]),
+		Spans::from(vec![
+			time("02:15:00"),
+			unit(),
+			Span::styled("retrying in 5 seconds", fg(Color::Yellow)),
+		]),
+		Spans::from(vec![
+			time("02:15:07"),
+			unit(),
+			search(Highlight::FocusedMatch),
+			Span::styled(": checksum mismatch in ", bold(Color::Red)),
+			Span::styled("c", bold(Color::Red)),
+			highlight(colors, Highlight::ViCursor, "h"),
+			Span::styled("unk 42", bold(Color::Red)),
+		]),
+		Spans::from(vec![
+			time("02:31:40"),
+			unit(),
+			Span::raw("finished with 2 "),
+			search(Highlight::Match),
+			Span::raw("s"),
+		]),
+		Spans::from(Span::raw("")),
+		Spans::from(Span::styled(
+			"Search: error                                                    ",
+			Style::default().add_modifier(Modifier::REVERSED),
+		)),
+	]
+}
@@ -0,0 +1,98 @@
+//! The palette panel: every color of the theme as a swatch with its hex value.
+
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+
+use crate::structured_data::structs::{ColorScheme, Colors};
+use crate::utils::color::{hex, parse_hex, Highlight, Rgb};
+use crate::utils::example::rgb;
+
+/// Width of a palette column: index, swatch and hex value.
+const COLUMN: usize = 15;
+/// Width of the labels of the other colors.
+const LABEL: usize = 10;
+
+/// The lines of the palette panel: the normal, bright and dim colors by ANSI index, then the
+/// primary, cursor, selection, search and hint colors as text on their background, two a line.
+pub fn swatches(colors: &Colors) -> Vec<Spans<'static>> {
+	let header = format!("{:9}{:COLUMN$}{:COLUMN$}{}", "", "   normal", "   bright", "   dim");
+	let mut lines = vec![Spans::from(Span::styled(
+		header,
+		Style::default().add_modifier(Modifier::BOLD),
+	))];
+
+	let schemes = [
+		(Some(0), colors.normal.as_ref()),
+		(Some(8), colors.bright.as_ref()),
+		(None, colors.dim.as_ref()),
+	];
+	for (slot, (name, _)) in ColorScheme::default().slots().into_iter().enumerate() {
+		let mut spans = vec![Span::raw(format!("{name:9}"))];
+		for (first, scheme) in schemes {
+			let index = first.map_or_else(String::new, |first: usize| (first + slot).to_string());
+			let color = scheme.and_then(|scheme| scheme.slots()[slot].1).and_then(parse_hex);
+			spans.push(Span::raw(format!("{index:>2} ")));
+			spans.push(swatch(color));
+			spans.push(Span::raw(format!(
+				" {:width$}",
+				color.map_or_else(|| "-".to_string(), hex),
+				width = COLUMN - 6
+			)));
+		}
+		lines.push(Spans::from(spans));
+	}
+
+	let pairs: Vec<Vec<Span<'static>>> = cell_pairs(colors)
+		.into_iter()
+		.map(|(label, foreground, background)| {
+			let mut style = Style::default();
+			if let Some(foreground) = foreground {
+				style = style.fg(rgb(foreground));
+			}
+			if let Some(background) = background {
+				style = style.bg(rgb(background));
+			}
+			let describe = |color: Option<Rgb>| color.map_or_else(|| "-".to_string(), hex);
+			vec![
+				Span::raw(format!("{label:LABEL$} ")),
+				Span::styled(" Ab ", style),
+				Span::raw(format!(" {}/{}  ", describe(foreground), describe(background))),
+			]
+		})
+		.collect();
+	for pair in pairs.chunks(2) {
+		lines.push(Spans::from(pair.concat()));
+	}
+	lines
+}
+
+fn swatch(color: Option<Rgb>) -> Span<'static> {
+	match color {
+		Some(color) => Span::styled("  ", Style::default().bg(rgb(color))),
+		None => Span::raw("  "),
+	}
+}
+
+/// The colors given as text on a background, by label, foreground and background. Highlights the
+/// theme leaves unset are left out.
+fn cell_pairs(colors: &Colors) -> Vec<(&'static str, Option<Rgb>, Option<Rgb>)> {
+	let background = colors.background().and_then(parse_hex);
+	let mut pairs = vec![("primary", colors.foreground().and_then(parse_hex), background)];
+	if let Some(primary) = &colors.primary {
+		for (label, foreground) in [
+			("dim fg", &primary.dim_foreground),
+			("bright fg", &primary.bright_foreground),
+		] {
+			if let Some(foreground) = foreground {
+				pairs.push((label, parse_hex(foreground), background));
+			}
+		}
+	}
+	for highlight in Highlight::ALL {
+		if highlight.given(colors).is_some() {
+			let (text, background) = highlight.colors(colors);
+			pairs.push((highlight.label(), text, background));
+		}
+	}
+	pairs
+}